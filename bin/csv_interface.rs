@@ -1,63 +1,1058 @@
-use scoring::{Analysis, Event, RawEvent};
+use scoring::{
+    event::{
+        wrappers::{Amount, Client, Currency, Id, Rounding, Tx},
+        EventType, ParseConfig, ParseEventError,
+    },
+    AccountSummary, Analysis, AnalysisConfig, AnalysisError, AnalysisSummary, Event, RawEvent,
+    SnapshotError, SummaryOrder,
+};
 
 use std::{
-    env,
-    io::{stderr, stdout},
+    fmt,
+    fs::File,
+    io::{self, stderr, stdin, stdout, BufRead, BufReader, BufWriter},
+    num::NonZeroU64,
+    path::Path,
+    str::FromStr,
 };
 
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
 use tracing_subscriber::EnvFilter;
 
+/// Output CSV record. One per client and currency.
+/// Amounts are rendered with fixed 4 decimal places
+#[derive(Debug, Serialize)]
+struct AccountRecord<C> {
+    client: Client<C>,
+    /// Column is written only if some balance is in a currency (see [has_currencies]).
+    /// Empty for balances without a currency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    currency: Option<Option<Currency>>,
+    available: String,
+    held: String,
+    total: String,
+    locked: bool,
+}
+
+impl<C> AccountRecord<C> {
+    fn new(summary: AccountSummary<C>, with_currency: bool) -> Self {
+        Self {
+            total: summary.total().to_string(),
+            available: summary.available.to_string(),
+            held: summary.held.to_string(),
+            client: summary.client,
+            currency: with_currency.then_some(summary.currency),
+            locked: summary.locked,
+        }
+    }
+}
+
+/// Whether any balance is in a currency. Otherwise output keeps the single currency format
+/// (without the currency column)
+fn has_currencies<C: Id, T: Id>(analysis: &Analysis<C, T>) -> bool {
+    analysis
+        .iter_accounts()
+        .any(|account| account.balances().any(|(currency, _)| currency.is_some()))
+}
+
+/// Rejected event record. Written to the rejects CSV (if requested)
+#[derive(Debug, Serialize)]
+struct RejectRecord<'a, C, T> {
+    /// Input file the event comes from (`-` for stdin)
+    file: &'a str,
+    /// Line number of the event in the input file
+    row: u64,
+    /// Empty for malformed rows
+    #[serde(rename = "type")]
+    ty: Option<EventType>,
+    /// Empty for malformed rows
+    client: Option<Client<C>>,
+    tx: Option<Tx<T>>,
+    /// Raw input text for rows with an invalid or unexpected amount (like `NaN`)
+    amount: Option<String>,
+    currency: Option<Currency>,
+    /// Stable [error code][AnalysisError::code], [row error code][RowError::code]
+    /// or [UNEXPECTED_FIELD_CODE]
+    code: &'static str,
+    /// Unexpected field (like `amount` of a dispute)
+    field: Option<&'static str>,
+    /// Raw input value of the unexpected field
+    value: Option<String>,
+}
+
+const REJECT_RECORD_HEADER: [&str; 10] = [
+    "file", "row", "type", "client", "tx", "amount", "currency", "code", "field", "value",
+];
+
+impl<'a, C: Id, T: Id> RejectRecord<'a, C, T> {
+    fn new(file: &'a str, row: u64, event: &Event<C, T>, err: &AnalysisError<C, T>) -> Self {
+        Self {
+            file,
+            row,
+            ty: Some(event.ty()),
+            client: Some(event.client()),
+            tx: event.tx(),
+            amount: event.amount().as_ref().map(Amount::to_string),
+            currency: event.currency(),
+            code: err.code(),
+            field: None,
+            value: None,
+        }
+    }
+
+    /// Warning about a field ignored by the event. The event itself is still processed
+    fn unexpected_field(
+        file: &'a str,
+        row: u64,
+        event: &Event<C, T>,
+        field: &'static str,
+        value: &str,
+    ) -> Self {
+        Self {
+            file,
+            row,
+            ty: Some(event.ty()),
+            client: Some(event.client()),
+            tx: event.tx(),
+            amount: event.amount().as_ref().map(Amount::to_string),
+            currency: event.currency(),
+            code: UNEXPECTED_FIELD_CODE,
+            field: None,
+            value: None,
+        }
+        .with_unexpected(field, value)
+    }
+
+    /// Fills in the unexpected field. Its value is also written to the column of the field
+    /// (if there is one) since the event itself does not carry it
+    fn with_unexpected(mut self, field: &'static str, value: &str) -> Self {
+        match field {
+            "amount" => self.amount = Some(value.to_string()),
+            "currency" => self.currency = value.parse().ok(),
+            _ => {}
+        }
+
+        self.field = Some(field);
+        self.value = Some(value.to_string());
+        self
+    }
+
+    /// Row which is not a valid event. Fields parsed before the failure are filled in
+    fn malformed(file: &'a str, row: u64, err: &RowError<C, T>) -> Self {
+        let (ty, client, tx) = match err {
+            RowError::Deserialize { .. } => (None, None, None),
+            RowError::Event(err) => (Some(err.ty()), err.client().cloned(), err.tx().cloned()),
+        };
+        let amount = match err {
+            RowError::Event(ParseEventError::InvalidAmount { source, .. }) => {
+                Some(source.input().to_string())
+            }
+            RowError::Event(ParseEventError::AmountAboveMaximum { input, .. }) => {
+                Some(input.clone())
+            }
+            _ => None,
+        };
+
+        let record = Self {
+            file,
+            row,
+            ty,
+            client,
+            tx,
+            amount,
+            currency: None,
+            code: err.code(),
+            field: None,
+            value: None,
+        };
+
+        match err {
+            RowError::Event(ParseEventError::UnexpectedField { field, value, .. }) => {
+                record.with_unexpected(field, value)
+            }
+            _ => record,
+        }
+    }
+}
+
+/// Code of rejects written for rows which could not be deserialized
+const MALFORMED_ROW_CODE: &str = "E_MALFORMED_ROW";
+
+/// Code of rows with fields ignored by the event. Matches the one of [ParseEventError::code]
+/// so that the field is reported the same way whether the event is processed or rejected
+const UNEXPECTED_FIELD_CODE: &str = "E_UNEXPECTED_FIELD";
+
+/// Event read from input
+#[derive(Debug)]
+struct InputEvent<C, T> {
+    event: Event<C, T>,
+    /// Fields given in input but not used by the event (see [RawEvent::unexpected_fields])
+    /// along with their raw values
+    unexpected_fields: Vec<(&'static str, String)>,
+}
+
+impl<C: Id, T: Id> InputEvent<C, T> {
+    fn parse(raw: RawEvent<C, T>, config: &ParseConfig) -> Result<Self, RowError<C, T>> {
+        let unexpected_fields = raw
+            .unexpected_fields()
+            .into_iter()
+            .map(|field| (field, raw.value(field).unwrap_or_default()))
+            .collect();
+
+        Ok(Self {
+            event: raw.into_event(config)?,
+            unexpected_fields,
+        })
+    }
+}
+
+/// Input row which could not be parsed into an event
+#[derive(Debug)]
+struct MalformedRow<C, T> {
+    /// Position of the row in the input. Only line is known for JSON Lines input
+    position: csv::Position,
+    error: RowError<C, T>,
+}
+
+/// Reason of an input row not being a valid event
+#[derive(Debug, thiserror::Error)]
+enum RowError<C, T> {
+    /// Row could not be deserialized (like one of unexpected length or with unknown type).
+    /// Column holding the invalid value is known for CSV input
+    #[error("{error}")]
+    Deserialize {
+        error: eyre::Report,
+        field: Option<String>,
+    },
+    /// Row has been deserialized but does not pass validation
+    #[error(transparent)]
+    Event(#[from] ParseEventError<C, T>),
+}
+
+impl<C: Id, T: Id> RowError<C, T> {
+    fn csv(error: csv::Error, headers: &csv::StringRecord, record: &csv::StringRecord) -> Self {
+        let field = match error.kind() {
+            csv::ErrorKind::Deserialize { err, .. } => match err.field() {
+                Some(index) => headers.get(index as usize).map(str::to_string),
+                // Unknown event type is reported by serde without the column
+                None => headers
+                    .iter()
+                    .position(|header| header == "type")
+                    .and_then(|index| record.get(index))
+                    .filter(|ty| {
+                        csv::StringRecord::from(vec![*ty])
+                            .deserialize::<(EventType,)>(None)
+                            .is_err()
+                    })
+                    .map(|_| "type".to_string()),
+            },
+            _ => None,
+        };
+
+        Self::Deserialize {
+            error: error.into(),
+            field,
+        }
+    }
+
+    fn json(error: serde_json::Error, text: &str) -> Self {
+        // Fields are checked one by one only to tell which of them is invalid
+        let field = serde_json::from_str::<serde_json::Value>(text)
+            .ok()
+            .and_then(|value| {
+                let invalid = |field: &str, check: fn(serde_json::Value) -> bool| {
+                    value.get(field).cloned().is_some_and(|value| !check(value))
+                };
+
+                if invalid("type", |value| EventType::deserialize(value).is_ok()) {
+                    Some("type")
+                } else if invalid("client", |value| Client::<C>::deserialize(value).is_ok()) {
+                    Some("client")
+                } else if invalid("tx", |value| Option::<Tx<T>>::deserialize(value).is_ok()) {
+                    Some("tx")
+                } else {
+                    None
+                }
+            });
+
+        Self::Deserialize {
+            error: error.into(),
+            field: field.map(str::to_string),
+        }
+    }
+
+    /// Stable code written to rejects: [MALFORMED_ROW_CODE], one of invalid ID or type
+    /// (for values which could not be deserialized) or [ParseEventError::code]
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Deserialize { field, .. } => match field.as_deref() {
+                Some("type") => "E_INVALID_TYPE",
+                Some("client") => "E_INVALID_CLIENT",
+                Some("tx") => "E_INVALID_TX",
+                _ => MALFORMED_ROW_CODE,
+            },
+            Self::Event(err) => err.code(),
+        }
+    }
+}
+
+/// Input event or malformed row along with its row (line number in the input)
+type InputRow<C, T> = (u64, Result<InputEvent<C, T>, MalformedRow<C, T>>);
+
+/// Format of input events
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InputFormat {
+    /// CSV with a header, one event per row
+    Csv,
+    /// JSON Lines, one [internally tagged][Event] event per line
+    Jsonl,
+}
+
+impl InputFormat {
+    /// Detects format from file extension. Defaults to [InputFormat::Csv].
+    /// Compression extension (like in `events.jsonl.gz`) is skipped
+    fn detect(path: &str) -> Self {
+        let path = Path::new(path);
+        let path = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz" | "zst") => Path::new(path.file_stem().unwrap_or_default()),
+            _ => path,
+        };
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("jsonl" | "ndjson") => Self::Jsonl,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// Compression of input detected by magic bytes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    const GZIP_MAGIC: &'static [u8] = &[0x1f, 0x8b];
+    const ZSTD_MAGIC: &'static [u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+    fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(Self::GZIP_MAGIC) {
+            Some(Self::Gzip)
+        } else if header.starts_with(Self::ZSTD_MAGIC) {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+/// Wraps input into a streaming decoder if it is compressed.
+/// Magic bytes are peeked without being consumed
+fn decompressed<'a>(input: Box<dyn io::Read + 'a>) -> eyre::Result<Box<dyn io::Read + 'a>> {
+    let mut input = BufReader::new(input);
+
+    // Magic bytes are expected to fit into a single read. Short reads can only make
+    // compressed input to be treated as a plain one which is then rejected by the parser
+    let compression = Compression::detect(input.fill_buf()?);
+
+    match compression {
+        None => Ok(Box::new(input)),
+        #[cfg(feature = "compression")]
+        Some(Compression::Gzip) => Ok(Box::new(flate2::bufread::MultiGzDecoder::new(input))),
+        #[cfg(feature = "compression")]
+        Some(Compression::Zstd) => Ok(Box::new(zstd::stream::read::Decoder::with_buffer(input)?)),
+        #[cfg(not(feature = "compression"))]
+        Some(compression) => eyre::bail!(
+            "{:?} compressed input requires `compression` feature to be enabled",
+            compression
+        ),
+    }
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "jsonl" => Ok(Self::Jsonl),
+            _ => Err(format!("unknown input format: {:?}", s)),
+        }
+    }
+}
+
+/// Format of account summaries output
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum OutputFormat {
+    /// CSV with a header, one account per row
+    #[default]
+    Csv,
+    /// JSON Lines, one account per line
+    Jsonl,
+    /// Single JSON array of accounts
+    Json,
+}
+
+impl OutputFormat {
+    /// Detects format from file extension
+    fn detect(path: &str) -> Option<Self> {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => Some(Self::Csv),
+            Some("jsonl" | "ndjson") => Some(Self::Jsonl),
+            Some("json") => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "jsonl" => Ok(Self::Jsonl),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown output format: {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::Jsonl => write!(f, "jsonl"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Sequential reader of input events
+enum EventReader<R: io::Read> {
+    Csv {
+        reader: csv::Reader<R>,
+        headers: csv::StringRecord,
+        record: csv::StringRecord,
+    },
+    Jsonl {
+        lines: io::Lines<BufReader<R>>,
+        line: u64,
+    },
+}
+
+impl<R: io::Read> EventReader<R> {
+    fn new(reader: R, format: InputFormat, dialect: CsvDialect) -> eyre::Result<Self> {
+        match format {
+            InputFormat::Csv => {
+                let mut reader = csv::ReaderBuilder::new()
+                    .trim(csv::Trim::All)
+                    .delimiter(dialect.delimiter)
+                    .has_headers(dialect.has_headers)
+                    .from_reader(reader);
+
+                let headers = match dialect.has_headers {
+                    true => reader
+                        .headers()
+                        .map_err(|err| {
+                            eyre::Report::from(err).wrap_err("failed to read csv headers")
+                        })?
+                        .clone(),
+                    false => csv::StringRecord::from(DEFAULT_INPUT_HEADER.to_vec()),
+                };
+
+                Ok(Self::Csv {
+                    reader,
+                    headers,
+                    record: csv::StringRecord::new(),
+                })
+            }
+            InputFormat::Jsonl => Ok(Self::Jsonl {
+                lines: BufReader::new(reader).lines(),
+                line: 0,
+            }),
+        }
+    }
+
+    /// Returns next event along with its row (line number in the input).
+    ///
+    /// Rows which can not be parsed or do not pass `config` validation are returned
+    /// as [MalformedRow] so that the caller decides whether to continue.
+    /// Only failures of the input itself are errors
+    fn next_event<C: Id, T: Id>(
+        &mut self,
+        config: &ParseConfig,
+    ) -> eyre::Result<Option<InputRow<C, T>>> {
+        match self {
+            Self::Csv {
+                reader,
+                headers,
+                record,
+            } => {
+                let event = match reader.read_record(record) {
+                    Ok(false) => return Ok(None),
+                    // Intermediate representation is used for event deserialization
+                    Ok(true) => record
+                        .deserialize::<RawEvent<C, T>>(Some(headers))
+                        .map_err(|err| RowError::csv(err, headers, record))
+                        .and_then(|raw| InputEvent::parse(raw, config))
+                        .map_err(|error| MalformedRow {
+                            position: record
+                                .position()
+                                .unwrap_or_else(|| reader.position())
+                                .clone(),
+                            error,
+                        }),
+                    Err(err) if err.is_io_error() => return Err(err.into()),
+                    // Reader stays usable after a malformed record (like one of unexpected length)
+                    Err(err) => Err(MalformedRow {
+                        position: err.position().unwrap_or_else(|| reader.position()).clone(),
+                        error: RowError::csv(err, headers, record),
+                    }),
+                };
+
+                let row = match &event {
+                    Ok(_) => record.position().map_or(0, csv::Position::line),
+                    Err(malformed) => malformed.position.line(),
+                };
+
+                Ok(Some((row, event)))
+            }
+            Self::Jsonl { lines, line } => loop {
+                let text = match lines.next() {
+                    Some(text) => text?,
+                    None => return Ok(None),
+                };
+                *line += 1;
+
+                if text.trim().is_empty() {
+                    continue;
+                }
+
+                // Events are read through the intermediate representation
+                // so that they are validated the same way as CSV ones
+                let event = RawEvent::<C, T>::from_json(&text)
+                    .map_err(|err| RowError::json(err, &text))
+                    .and_then(|raw| InputEvent::parse(raw, config))
+                    .map_err(|error| {
+                        let mut position = csv::Position::new();
+                        position.set_line(*line);
+
+                        MalformedRow { position, error }
+                    });
+
+                return Ok(Some((*line, event)));
+            },
+        }
+    }
+}
+
+/// Processes event logs and outputs resulting accounts.
+///
+/// Basic usage: `csv_interface input.csv > output.csv`
+#[derive(Debug, StructOpt)]
+#[structopt(name = "csv_interface")]
+struct Args {
+    /// Input files processed in order as a single log. `-` stands for stdin
+    #[structopt(required = true)]
+    inputs: Vec<String>,
+    /// Output file [default: stdout]
+    #[structopt(short, long)]
+    output: Option<String>,
+    /// Input format: csv|jsonl [default: detected from input file extension]
+    #[structopt(long)]
+    input_format: Option<InputFormat>,
+    /// Output format: csv|jsonl|json [default: detected from output file extension]
+    #[structopt(long)]
+    output_format: Option<OutputFormat>,
+    /// Delimiter of input and output CSV
+    #[structopt(long, default_value = ",", parse(try_from_str = parse_delimiter))]
+    delimiter: u8,
+    /// Input CSV has no header. Columns are expected in `type,client,tx,amount,reason,currency` order
+    #[structopt(long)]
+    no_header: bool,
+    /// Do not write header to output CSV
+    #[structopt(long)]
+    no_output_header: bool,
+    /// Order of output accounts: client|total
+    #[structopt(long, default_value = "client")]
+    order: SummaryOrder,
+    /// What to do when an event is rejected by analysis: continue|abort
+    #[structopt(long, default_value = "continue")]
+    failure_policy: FailurePolicy,
+    /// How rows which are not valid events are treated: strict|lenient
+    #[structopt(long, default_value = "strict")]
+    parse_mode: ParseMode,
+    /// Handling of fields not used by the event type
+    /// (like `amount` of a dispute): ignore|warn|reject
+    #[structopt(long, default_value = "ignore")]
+    unexpected_fields: UnexpectedFields,
+    /// Treat events with amount magnitude above this value as malformed
+    #[structopt(long)]
+    max_amount: Option<Amount>,
+    /// Treat events with amounts of more than 4 decimal places as malformed instead of rounding them
+    #[structopt(long)]
+    reject_imprecise_amounts: bool,
+    /// Type of client IDs: u16|u32|u64|string.
+    /// Textual IDs (like UUIDs) are compared and ordered as strings
+    #[structopt(long, default_value = "u16")]
+    client_ids: ClientIds,
+    /// Type of transaction IDs: u32|u64|string
+    #[structopt(long, default_value = "u32")]
+    tx_ids: TxIds,
+    /// Log filter (like `info` or `csv_interface=debug`) [default: RUST_LOG variable]
+    #[structopt(long)]
+    log_level: Option<String>,
+    /// Write rejected events to this CSV file
+    #[structopt(long = "rejects")]
+    rejects_path: Option<String>,
+    /// Restore analysis state from this snapshot before processing
+    #[structopt(long = "snapshot-in")]
+    snapshot_in_path: Option<String>,
+    /// Save analysis state to this snapshot after processing
+    #[structopt(long = "snapshot-out")]
+    snapshot_out_path: Option<String>,
+    /// Write intermediate balances every N events
+    #[structopt(long)]
+    report_every: Option<NonZeroU64>,
+    /// Prefix of intermediate balances files
+    #[structopt(long, default_value = "report")]
+    report_prefix: String,
+}
+
+fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s.as_bytes() {
+        [delimiter] => Ok(*delimiter),
+        _ => Err(format!(
+            "delimiter must be a single ASCII character: {:?}",
+            s
+        )),
+    }
+}
+
+/// What to do when an event is rejected by analysis
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FailurePolicy {
+    /// Log rejection and continue processing
+    Continue,
+    /// Stop processing and exit with an error
+    Abort,
+}
+
+impl FromStr for FailurePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "continue" => Ok(Self::Continue),
+            "abort" => Ok(Self::Abort),
+            _ => Err(format!("unknown failure policy: {:?}", s)),
+        }
+    }
+}
+
+/// How rows which can not be parsed into an event are treated
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParseMode {
+    /// Stop at the first malformed row and exit with an error. No output is written
+    Strict,
+    /// Log malformed row, record it to rejects (if requested) and continue processing
+    Lenient,
+}
+
+impl FromStr for ParseMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(Self::Strict),
+            "lenient" => Ok(Self::Lenient),
+            _ => Err(format!("unknown parse mode: {:?}", s)),
+        }
+    }
+}
+
+/// Handling of fields not used by the event type
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnexpectedFields {
+    /// Silently ignore them
+    Ignore,
+    /// Log them, record them to rejects (if requested) and process the event
+    Warn,
+    /// Treat the row as malformed
+    Reject,
+}
+
+impl FromStr for UnexpectedFields {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ignore" => Ok(Self::Ignore),
+            "warn" => Ok(Self::Warn),
+            "reject" => Ok(Self::Reject),
+            _ => Err(format!("unknown unexpected fields handling: {:?}", s)),
+        }
+    }
+}
+
+/// Type of client IDs
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ClientIds {
+    U16,
+    U32,
+    U64,
+    String,
+}
+
+impl FromStr for ClientIds {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "u16" => Ok(Self::U16),
+            "u32" => Ok(Self::U32),
+            "u64" => Ok(Self::U64),
+            "string" => Ok(Self::String),
+            _ => Err(format!("unknown client ids type: {:?}", s)),
+        }
+    }
+}
+
+/// Type of transaction IDs
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TxIds {
+    U32,
+    U64,
+    String,
+}
+
+impl FromStr for TxIds {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "u32" => Ok(Self::U32),
+            "u64" => Ok(Self::U64),
+            "string" => Ok(Self::String),
+            _ => Err(format!("unknown tx ids type: {:?}", s)),
+        }
+    }
+}
+
+/// CSV layout of input or output
+#[derive(Debug, Clone, Copy)]
+struct CsvDialect {
+    delimiter: u8,
+    has_headers: bool,
+}
+
+/// Input CSV columns assumed when input has no header
+const DEFAULT_INPUT_HEADER: [&str; 6] = ["type", "client", "tx", "amount", "reason", "currency"];
+
+/// Writes [summary][AnalysisSummary] in specified [format][OutputFormat].
+/// Currency is written only if requested
+fn write_summary<W: io::Write, C: Id>(
+    mut writer: W,
+    summary: AnalysisSummary<C>,
+    with_currency: bool,
+    format: OutputFormat,
+    dialect: CsvDialect,
+) -> eyre::Result<()> {
+    match format {
+        OutputFormat::Csv => {
+            let mut csv_writer = csv::WriterBuilder::new()
+                .delimiter(dialect.delimiter)
+                .has_headers(dialect.has_headers)
+                .from_writer(writer);
+
+            // Output analysis summary sequentially
+            for account_summary in summary {
+                csv_writer
+                    .serialize(AccountRecord::new(account_summary, with_currency))
+                    .map_err(|err| {
+                        eyre::Report::from(err).wrap_err("failed to write csv record")
+                    })?;
+            }
+
+            // Flush csv writer
+            csv_writer
+                .flush()
+                .map_err(|err| eyre::Report::from(err).wrap_err("failed to flush csv writer"))?;
+        }
+        OutputFormat::Jsonl => {
+            // Output analysis summary sequentially
+            for account_summary in summary {
+                let record = AccountRecord::new(account_summary, with_currency);
+                serde_json::to_writer(&mut writer, &record)?;
+                writer.write_all(b"\n")?;
+            }
+
+            writer.flush()?;
+        }
+        OutputFormat::Json => {
+            let records = summary
+                .map(|account_summary| AccountRecord::new(account_summary, with_currency))
+                .collect::<Vec<_>>();
+
+            serde_json::to_writer(&mut writer, &records)?;
+            writer.write_all(b"\n")?;
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
 fn main() -> eyre::Result<()> {
+    let args = Args::from_args();
+
     // Setup tracing
     //
     // NOTE: Logs are forwarded to stderr.
     // This way they are not mixed out with the program output
     let (non_blocking, _guard) = tracing_appender::non_blocking(stderr());
-    // Logs are configured from standard RUST_LOG variable
+    // Logs are configured from standard RUST_LOG variable unless log level is specified
+    let env_filter = match args.log_level.as_deref() {
+        Some(log_level) => EnvFilter::try_new(log_level)?,
+        None => EnvFilter::from_default_env(),
+    };
     tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
+        .with_env_filter(env_filter)
         .with_writer(non_blocking)
         .init();
 
-    // Get input file path
-    let file_path = env::args()
-        .nth(1) // skip executable name and take first effective argument
-        .ok_or_else(|| eyre::eyre!("Input file name expected"))?;
+    // Analysis is monomorphized for the requested ID types
+    // so that the default (small) IDs do not pay for the wider ones
+    match args.client_ids {
+        ClientIds::U16 => run_with_client::<u16>(args),
+        ClientIds::U32 => run_with_client::<u32>(args),
+        ClientIds::U64 => run_with_client::<u64>(args),
+        ClientIds::String => run_with_client::<String>(args),
+    }
+}
+
+fn run_with_client<C: Id>(args: Args) -> eyre::Result<()> {
+    match args.tx_ids {
+        TxIds::U32 => run::<C, u32>(args),
+        TxIds::U64 => run::<C, u64>(args),
+        TxIds::String => run::<C, String>(args),
+    }
+}
+
+/// Processes inputs with clients and transactions identified by `C` and `T`
+fn run<C: Id, T: Id>(args: Args) -> eyre::Result<()> {
+    let input_dialect = CsvDialect {
+        delimiter: args.delimiter,
+        has_headers: !args.no_header,
+    };
+    let output_dialect = CsvDialect {
+        delimiter: args.delimiter,
+        has_headers: !args.no_output_header,
+    };
+    let mut parse_config = ParseConfig::default();
+    if let Some(max_amount) = args.max_amount {
+        parse_config = parse_config.max_amount(max_amount);
+    }
+    if args.reject_imprecise_amounts {
+        parse_config = parse_config.rounding(Rounding::Reject);
+    }
+    if args.unexpected_fields == UnexpectedFields::Reject {
+        parse_config = parse_config.reject_unexpected_fields(true);
+    }
+    let output_format = args
+        .output_format
+        .or_else(|| args.output.as_deref().and_then(OutputFormat::detect))
+        .unwrap_or_default();
+
+    // Init analysis. State of previous runs is restored from snapshot (if provided)
+    let mut analysis = match args.snapshot_in_path {
+        Some(path) => File::open(path)
+            .map_err(SnapshotError::from)
+            .and_then(|file| Analysis::restore(BufReader::new(file), AnalysisConfig::default()))
+            .map_err(|err| eyre::Report::from(err).wrap_err("failed to restore snapshot"))?,
+        None => Analysis::<C, T>::default(),
+    };
+
+    // Init rejects csv writer
+    let mut rejects_writer = args
+        .rejects_path
+        .map(|path| {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_path(path)?;
+            // Header is written explicitly so that it is present even if nothing is rejected
+            writer.write_record(REJECT_RECORD_HEADER)?;
+            Ok(writer)
+        })
+        .transpose()
+        .map_err(|err: csv::Error| {
+            eyre::Report::from(err).wrap_err("failed to init rejects csv writer")
+        })?;
+
+    let mut events_count = 0;
+    let mut malformed_count = 0;
+
+    // Process input files in order
+    for file_path in args.inputs.iter() {
+        // Init events reader
+        let input_format = args
+            .input_format
+            .unwrap_or_else(|| InputFormat::detect(file_path));
+        let input: Box<dyn io::Read> = match file_path.as_str() {
+            "-" => Box::new(stdin().lock()),
+            path => Box::new(File::open(path).map_err(|err| {
+                eyre::Report::from(err).wrap_err(format!("failed to open input file {}", path))
+            })?),
+        };
+        let input = decompressed(input)
+            .map_err(|err| err.wrap_err(format!("failed to read {}", file_path)))?;
+        let mut event_reader = EventReader::new(input, input_format, input_dialect)
+            .map_err(|err| err.wrap_err(format!("failed to read {}", file_path)))?;
+
+        // Process events sequentially
+        while let Some((row, event)) = event_reader
+            .next_event::<C, T>(&parse_config)
+            .map_err(|err| err.wrap_err(format!("failed to read event from {}", file_path)))?
+        {
+            let InputEvent {
+                event,
+                unexpected_fields,
+            } = match event {
+                Ok(event) => event,
+                Err(MalformedRow { position, error }) => {
+                    if args.parse_mode == ParseMode::Strict {
+                        return Err(eyre::Report::from(error)
+                            .wrap_err(format!("invalid event on line {}", row))
+                            .wrap_err(format!("failed to read {}", file_path)));
+                    }
+
+                    tracing::warn!(
+                        "malformed row skipped: {}:{} (byte: {}, record: {}): {}",
+                        file_path,
+                        position.line(),
+                        position.byte(),
+                        position.record(),
+                        error
+                    );
+                    malformed_count += 1;
+
+                    if let Some(rejects_writer) = rejects_writer.as_mut() {
+                        rejects_writer
+                            .serialize(RejectRecord::malformed(file_path, row, &error))
+                            .map_err(|err| {
+                                eyre::Report::from(err)
+                                    .wrap_err("failed to write rejects csv record")
+                            })?;
+                    }
 
-    // Init csv reader
-    let mut csv_reader = csv::ReaderBuilder::new()
-        .trim(csv::Trim::All)
-        .from_path(file_path)
-        .map_err(|err| eyre::Report::from(err).wrap_err("failed to init csv reader"))?;
+                    continue;
+                }
+            };
 
-    // Init analysis
-    let mut analysis = Analysis::begin();
+            if args.unexpected_fields == UnexpectedFields::Warn {
+                for (field, value) in unexpected_fields {
+                    tracing::warn!(
+                        "unexpected '{}' ignored ({:?}): {}:{}: {:?}",
+                        field,
+                        value,
+                        file_path,
+                        row,
+                        event
+                    );
 
-    // Process events sequentially
-    while let Some(raw_event) = csv_reader.deserialize::<RawEvent>().next() {
-        // Intermediate representation is used for event deserialization
-        let raw_event = raw_event.map_err(eyre::Report::from)?;
-        let event = Event::try_from(raw_event)?;
+                    if let Some(rejects_writer) = rejects_writer.as_mut() {
+                        rejects_writer
+                            .serialize(RejectRecord::unexpected_field(
+                                file_path, row, &event, field, &value,
+                            ))
+                            .map_err(|err| {
+                                eyre::Report::from(err)
+                                    .wrap_err("failed to write rejects csv record")
+                            })?;
+                    }
+                }
+            }
 
-        let _ = analysis
-            .process_event(&event)
-            .map_err(|err| tracing::error!("analysis error: {}", err));
+            if let Err(err) = analysis.process_event(&event) {
+                tracing::error!("analysis error: {}:{}: {}", file_path, row, err);
+
+                if let Some(rejects_writer) = rejects_writer.as_mut() {
+                    rejects_writer
+                        .serialize(RejectRecord::new(file_path, row, &event, &err))
+                        .map_err(|err| {
+                            eyre::Report::from(err).wrap_err("failed to write rejects csv record")
+                        })?;
+                }
+
+                if args.failure_policy == FailurePolicy::Abort {
+                    if let Some(rejects_writer) = rejects_writer.as_mut() {
+                        rejects_writer.flush()?;
+                    }
+
+                    return Err(eyre::Report::from(err)
+                        .wrap_err(format!("event rejected: {}:{}", file_path, row)));
+                }
+            }
+
+            events_count += 1;
+
+            // Write intermediate balances report (if requested)
+            if let Some(every) = args.report_every {
+                if events_count % every.get() == 0 {
+                    let path = format!("{}-{}.{}", args.report_prefix, events_count, output_format);
+                    let file = File::create(&path).map_err(|err| {
+                        eyre::Report::from(err)
+                            .wrap_err(format!("failed to create report {}", path))
+                    })?;
+
+                    write_summary(
+                        BufWriter::new(file),
+                        analysis.current_summary(args.order),
+                        has_currencies(&analysis),
+                        output_format,
+                        output_dialect,
+                    )?;
+                }
+            }
+        }
     }
 
-    // Init csv writer
-    let mut csv_writer = csv::Writer::from_writer(stdout());
+    if malformed_count > 0 {
+        tracing::warn!("{} malformed rows skipped", malformed_count);
+    }
 
-    // Output analysis summary sequentially
-    for account_summary in analysis.summary() {
-        csv_writer
-            .serialize(account_summary)
-            .map_err(|err| eyre::Report::from(err).wrap_err("failed to write csv record"))?;
+    // Flush rejects csv writer
+    if let Some(mut rejects_writer) = rejects_writer {
+        rejects_writer.flush().map_err(|err| {
+            eyre::Report::from(err).wrap_err("failed to flush rejects csv writer")
+        })?;
     }
 
-    // Flush csv writer
-    csv_writer
-        .flush()
-        .map_err(|err| eyre::Report::from(err).wrap_err("failed to flush csv writer"))?;
+    // Save analysis state for subsequent runs (if requested)
+    if let Some(path) = args.snapshot_out_path {
+        File::create(path)
+            .map_err(SnapshotError::from)
+            .and_then(|file| analysis.snapshot(BufWriter::new(file)))
+            .map_err(|err| eyre::Report::from(err).wrap_err("failed to write snapshot"))?;
+    }
 
-    Ok(())
+    // Output goes to stdout unless output file is specified
+    let output: Box<dyn io::Write> = match args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(&path).map_err(|err| {
+            eyre::Report::from(err).wrap_err(format!("failed to create output file {}", path))
+        })?)),
+        None => Box::new(stdout()),
+    };
+
+    let with_currency = has_currencies(&analysis);
+    write_summary(
+        output,
+        analysis.summary_ordered(args.order),
+        with_currency,
+        output_format,
+        output_dialect,
+    )
 }
@@ -71,24 +71,37 @@ fn generate_event(rng: &mut ThreadRng, event_type: EventType, client: Client, tx
         EventType::Deposit => Event::Deposit {
             client,
             tx,
-            amount: Amount(rng.gen_range(0.0..1000.0)),
+            amount: Amount::from_scaled(rng.gen_range(0..1000 * Amount::SCALE)),
+            currency: None,
         },
         EventType::Withdrawal => Event::Withdrawal {
             client,
             tx,
-            amount: Amount(rng.gen_range(0.0..1000.0)),
+            amount: Amount::from_scaled(rng.gen_range(0..1000 * Amount::SCALE)),
+            currency: None,
         },
         EventType::Dispute => Event::Dispute {
             client,
             tx: Tx(rng.gen_range(1..tx.0)),
+            currency: None,
         },
         EventType::Resolve => Event::Resolve {
             client,
             tx: Tx(rng.gen_range(1..tx.0)),
+            currency: None,
         },
         EventType::Chargeback => Event::Chargeback {
             client,
             tx: Tx(rng.gen_range(1..tx.0)),
+            currency: None,
+        },
+        EventType::Freeze => Event::Freeze {
+            client,
+            reason: "generated".to_string(),
+        },
+        EventType::Unlock => Event::Unlock {
+            client,
+            reason: "generated".to_string(),
         },
     }
 }
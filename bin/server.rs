@@ -0,0 +1,610 @@
+use scoring::{
+    event::{
+        wrappers::{Client, Currency},
+        ParseConfig,
+    },
+    Analysis, AnalysisConfig, AnalysisError, AnalysisResult, Event, JournaledAnalysis, RawEvent,
+};
+
+#[cfg(unix)]
+use std::{
+    fs,
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+};
+use std::{
+    io::{stderr, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+use serde::Serialize;
+use structopt::{clap::ArgGroup, StructOpt};
+use tracing_subscriber::EnvFilter;
+
+/// Where the server listens for connections
+#[derive(Debug)]
+enum Listen {
+    #[cfg(unix)]
+    Unix(PathBuf),
+    Tcp(SocketAddr),
+}
+
+/// Keeps analysis resident and serves events and balance queries over a local socket.
+///
+/// Basic usage: `server --unix /tmp/scoring.sock`
+#[derive(Debug, StructOpt)]
+#[structopt(name = "server", group = ArgGroup::with_name("listen").required(true))]
+struct Args {
+    /// Listen on this Unix domain socket (Unix only)
+    #[structopt(long, group = "listen")]
+    unix: Option<PathBuf>,
+    /// Listen on this TCP address. Only localhost addresses are allowed
+    #[structopt(long, group = "listen", parse(try_from_str = parse_localhost))]
+    tcp: Option<SocketAddr>,
+    /// Journal accepted events to this directory and recover them on restart
+    #[structopt(long)]
+    state_dir: Option<PathBuf>,
+    /// Snapshot the state (and start a new journal) after this many journaled events.
+    /// 0 disables periodic checkpoints
+    #[structopt(long, default_value = "10000")]
+    checkpoint_every: usize,
+}
+
+impl Args {
+    fn listen(&self) -> eyre::Result<Listen> {
+        match (&self.unix, self.tcp) {
+            #[cfg(unix)]
+            (Some(path), _) => Ok(Listen::Unix(path.clone())),
+            #[cfg(not(unix))]
+            (Some(_), _) => eyre::bail!("unix sockets are not supported on this platform"),
+            (None, Some(addr)) => Ok(Listen::Tcp(addr)),
+            (None, None) => eyre::bail!("--unix or --tcp expected"),
+        }
+    }
+}
+
+fn parse_localhost(s: &str) -> Result<SocketAddr, String> {
+    let addr: SocketAddr = s.parse().map_err(|err| format!("{}: {:?}", err, s))?;
+
+    // The protocol has no authentication so it is never exposed to the network
+    if !addr.ip().is_loopback() {
+        return Err(format!("only localhost addresses are allowed: {}", addr));
+    }
+
+    Ok(addr)
+}
+
+/// Resident analysis. Journaled if state directory is provided
+#[derive(Debug)]
+enum Engine {
+    InMemory(Analysis),
+    /// Checkpoint is taken after every `checkpoint_every` journaled events (never if 0)
+    Journaled {
+        analysis: JournaledAnalysis,
+        checkpoint_every: usize,
+    },
+}
+
+impl Engine {
+    fn process_event(&mut self, event: &Event) -> eyre::Result<AnalysisResult<()>> {
+        match self {
+            Self::InMemory(analysis) => Ok(analysis.process_event(event)),
+            Self::Journaled {
+                analysis,
+                checkpoint_every,
+            } => {
+                let result = analysis.process_event(event)?;
+
+                if *checkpoint_every > 0 && analysis.journaled_events() >= *checkpoint_every {
+                    analysis.checkpoint()?;
+                }
+
+                Ok(result)
+            }
+        }
+    }
+
+    /// Takes checkpoint of journaled analysis. Returns `false` if there is no journal
+    fn checkpoint(&mut self) -> eyre::Result<bool> {
+        match self {
+            Self::InMemory(_) => Ok(false),
+            Self::Journaled { analysis, .. } => {
+                analysis.checkpoint()?;
+                Ok(true)
+            }
+        }
+    }
+
+    fn analysis(&self) -> &Analysis {
+        match self {
+            Self::InMemory(analysis) => analysis,
+            Self::Journaled { analysis, .. } => analysis.analysis(),
+        }
+    }
+}
+
+/// Reason for the server to stop
+#[derive(Debug)]
+enum Stop {
+    /// Engine failed and processing must not continue
+    Fatal(eyre::Report),
+    /// Shutdown has been requested
+    Shutdown,
+}
+
+/// Reply to a single request line. Serialized as a single JSON line
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum Reply {
+    /// Event has been accepted
+    Accepted,
+    /// Event has been rejected
+    Rejected { error: AnalysisError },
+    /// Account balance. Amounts are rendered with fixed 4 decimal places
+    Balance {
+        client: Client,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        currency: Option<Currency>,
+        available: String,
+        held: String,
+        total: String,
+        locked: bool,
+    },
+    /// Account (or its balance in requested currency) does not exist
+    NotFound {
+        client: Client,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        currency: Option<Currency>,
+    },
+    /// Checkpoint has been taken
+    Checkpointed,
+    /// Server stops after a final checkpoint
+    ShuttingDown,
+    /// Request could not be parsed
+    Invalid { message: String },
+}
+
+/// Handles single request line which is either an [Event] in JSON,
+/// `balance <client> [<currency>]` query, `checkpoint` or `shutdown`.
+/// Shutdown itself is left to the caller
+fn handle_request(engine: &Mutex<Engine>, request: &str) -> eyre::Result<Reply> {
+    match request {
+        "checkpoint" => {
+            let mut engine = engine.lock().map_err(|_| eyre::eyre!("engine poisoned"))?;
+
+            return Ok(match engine.checkpoint()? {
+                true => Reply::Checkpointed,
+                false => Reply::Invalid {
+                    message: "checkpoint requires --state-dir".to_string(),
+                },
+            });
+        }
+        "shutdown" => return Ok(Reply::ShuttingDown),
+        _ => {}
+    }
+
+    if let Some(query) = request.strip_prefix("balance ") {
+        let mut query = query.split_whitespace();
+
+        let client = match query.next().unwrap_or_default().parse() {
+            Ok(client) => Client(client),
+            Err(err) => {
+                return Ok(Reply::Invalid {
+                    message: format!("invalid client: {}", err),
+                })
+            }
+        };
+        // Balance without a currency is queried unless currency is specified
+        let currency = match query.next().map(str::parse).transpose() {
+            Ok(currency) => currency,
+            Err(err) => {
+                return Ok(Reply::Invalid {
+                    message: format!("invalid currency: {}", err),
+                })
+            }
+        };
+
+        let engine = engine.lock().map_err(|_| eyre::eyre!("engine poisoned"))?;
+
+        return Ok(match engine.analysis().account_summary(client, currency) {
+            Some(summary) => Reply::Balance {
+                client,
+                currency,
+                available: summary.available.to_string(),
+                held: summary.held.to_string(),
+                total: summary.total().to_string(),
+                locked: summary.locked,
+            },
+            None => Reply::NotFound { client, currency },
+        });
+    }
+
+    // Numeric amounts are read from their literal digits (see [RawEvent::from_json])
+    let event = RawEvent::from_json(request)
+        .map_err(|err| err.to_string())
+        .and_then(|raw| {
+            raw.into_event(&ParseConfig::default())
+                .map_err(|err| err.to_string())
+        });
+    let event: Event = match event {
+        Ok(event) => event,
+        Err(err) => {
+            return Ok(Reply::Invalid {
+                message: format!("invalid event: {}", err),
+            })
+        }
+    };
+
+    let mut engine = engine.lock().map_err(|_| eyre::eyre!("engine poisoned"))?;
+
+    match engine.process_event(&event)? {
+        Ok(()) => Ok(Reply::Accepted),
+        Err(error) => {
+            tracing::error!("analysis error: {}", error);
+            Ok(Reply::Rejected { error })
+        }
+    }
+}
+
+/// Serves single connection until the client closes it.
+/// Fatal engine error or shutdown request is sent to `stop` and ends the connection
+fn serve<S>(stream: S, engine: Arc<Mutex<Engine>>, stop: &mpsc::Sender<Stop>) -> eyre::Result<()>
+where
+    for<'a> &'a S: Read + Write,
+{
+    let mut writer = &stream;
+
+    for request in BufReader::new(&stream).lines() {
+        let request = request?;
+        let request = request.trim();
+
+        if request.is_empty() {
+            continue;
+        }
+
+        let reply = match handle_request(&engine, request) {
+            Ok(reply) => reply,
+            Err(err) => {
+                // Journal failure may leave a partially written record behind.
+                // Processing must not continue, so main shuts the server down
+                tracing::error!("fatal engine error: {:?}", err);
+                let _ = stop.send(Stop::Fatal(err));
+                return Ok(());
+            }
+        };
+
+        serde_json::to_writer(&mut writer, &reply)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+
+        if let Reply::ShuttingDown = reply {
+            let _ = stop.send(Stop::Shutdown);
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+fn spawn_connection<S>(stream: S, engine: &Arc<Mutex<Engine>>, stop: &mpsc::Sender<Stop>)
+where
+    S: Send + 'static,
+    for<'a> &'a S: Read + Write,
+{
+    let engine = Arc::clone(engine);
+    let stop = stop.clone();
+
+    thread::spawn(move || {
+        if let Err(err) = serve(stream, engine, &stop) {
+            tracing::warn!("connection error: {}", err);
+        }
+    });
+}
+
+fn main() -> eyre::Result<()> {
+    // Setup tracing
+    //
+    // NOTE: Logs are forwarded to stderr.
+    // Logs are configured from standard RUST_LOG variable
+    let (non_blocking, _guard) = tracing_appender::non_blocking(stderr());
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(non_blocking)
+        .init();
+
+    let args = Args::from_args();
+    let listen = args.listen()?;
+
+    // Init analysis. State is recovered from the state directory (if provided)
+    let engine = match args.state_dir {
+        Some(dir) => Engine::Journaled {
+            analysis: JournaledAnalysis::open(dir, AnalysisConfig::default())
+                .map_err(|err| eyre::Report::from(err).wrap_err("failed to recover analysis"))?,
+            checkpoint_every: args.checkpoint_every,
+        },
+        None => Engine::InMemory(Analysis::begin()),
+    };
+    let engine = Arc::new(Mutex::new(engine));
+    let (stop_sender, stop) = mpsc::channel();
+
+    // Connections are accepted in the background so that main is free to wait for a stop request
+    match listen {
+        #[cfg(unix)]
+        Listen::Unix(path) => {
+            // Socket left by a previous run is removed. Other files are never touched
+            if let Ok(metadata) = fs::symlink_metadata(&path) {
+                if metadata.file_type().is_socket() {
+                    fs::remove_file(&path)?;
+                }
+            }
+
+            let listener = UnixListener::bind(&path)
+                .map_err(|err| eyre::Report::from(err).wrap_err("failed to bind unix socket"))?;
+            tracing::info!("listening on unix socket: {}", path.display());
+
+            let engine = Arc::clone(&engine);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => spawn_connection::<UnixStream>(stream, &engine, &stop_sender),
+                        Err(err) => tracing::warn!("failed to accept connection: {}", err),
+                    }
+                }
+            });
+        }
+        Listen::Tcp(addr) => {
+            let listener = TcpListener::bind(addr)
+                .map_err(|err| eyre::Report::from(err).wrap_err("failed to bind tcp socket"))?;
+            tracing::info!("listening on tcp: {}", listener.local_addr()?);
+
+            let engine = Arc::clone(&engine);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => spawn_connection::<TcpStream>(stream, &engine, &stop_sender),
+                        Err(err) => tracing::warn!("failed to accept connection: {}", err),
+                    }
+                }
+            });
+        }
+    }
+
+    // Returning from main (unlike exiting right away) drops the guard, which flushes the logs.
+    // The channel is never closed since the listener keeps a sender for as long as it accepts
+    match stop.recv() {
+        Ok(Stop::Fatal(err)) => Err(err.wrap_err("fatal engine error")),
+        Ok(Stop::Shutdown) => {
+            // Events accepted by other connections after the checkpoint are journaled anyway
+            let mut engine = engine.lock().map_err(|_| eyre::eyre!("engine poisoned"))?;
+            engine.checkpoint()?;
+
+            tracing::info!("shutting down");
+            Ok(())
+        }
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use scoring::event::wrappers::Amount;
+
+    use assert_matches::assert_matches;
+
+    fn engine() -> Mutex<Engine> {
+        Mutex::new(Engine::InMemory(Analysis::begin()))
+    }
+
+    fn handle(engine: &Mutex<Engine>, request: &str) -> Reply {
+        handle_request(engine, request).unwrap()
+    }
+
+    fn state_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("scoring-server-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn deposit(tx: u32) -> String {
+        format!(
+            r#"{{"type":"deposit","client":1,"tx":{},"amount":"1"}}"#,
+            tx
+        )
+    }
+
+    #[test]
+    fn test_events() {
+        let engine = engine();
+
+        assert_matches!(
+            handle(
+                &engine,
+                r#"{"type":"deposit","client":1,"tx":1,"amount":"2.5"}"#
+            ),
+            Reply::Accepted
+        );
+        assert_matches!(
+            handle(
+                &engine,
+                r#"{"type":"deposit","client":1,"tx":2,"amount":1.5}"#
+            ),
+            Reply::Accepted
+        );
+        assert_matches!(
+            handle(
+                &engine,
+                r#"{"type":"withdrawal","client":1,"tx":3,"amount":1}"#
+            ),
+            Reply::Accepted
+        );
+        assert_matches!(
+            handle(
+                &engine,
+                r#"{"type":"deposit","client":1,"tx":1,"amount":"2.5"}"#
+            ),
+            Reply::Rejected {
+                error: AnalysisError::DuplicateOperation(_)
+            }
+        );
+        // Literal digits are kept, nearest f64 would be 0.12345 rounded up
+        assert_matches!(
+            handle(
+                &engine,
+                r#"{"type":"deposit","client":1,"tx":4,"amount":0.12344999999999999999}"#
+            ),
+            Reply::Accepted
+        );
+
+        let summary = engine
+            .lock()
+            .unwrap()
+            .analysis()
+            .account_summary(Client(1), None)
+            .unwrap();
+        assert_eq!(summary.available, Amount::from_scaled(31_234));
+    }
+
+    #[test]
+    fn test_balance() {
+        let engine = engine();
+
+        handle(
+            &engine,
+            r#"{"type":"deposit","client":1,"tx":1,"amount":"2.5"}"#,
+        );
+        handle(
+            &engine,
+            r#"{"type":"deposit","client":1,"tx":2,"amount":"1","currency":"usd"}"#,
+        );
+
+        assert_eq!(
+            serde_json::to_string(&handle(&engine, "balance 1")).unwrap(),
+            r#"{"status":"balance","client":1,"available":"2.5000","held":"0.0000","total":"2.5000","locked":false}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&handle(&engine, "balance 1 usd")).unwrap(),
+            r#"{"status":"balance","client":1,"currency":"USD","available":"1.0000","held":"0.0000","total":"1.0000","locked":false}"#
+        );
+        assert_matches!(
+            handle(&engine, "balance 1 eur"),
+            Reply::NotFound {
+                client: Client(1),
+                currency: Some(_)
+            }
+        );
+        assert_matches!(
+            handle(&engine, "balance 2"),
+            Reply::NotFound {
+                client: Client(2),
+                currency: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_balance_query() {
+        let engine = engine();
+
+        assert_matches!(
+            handle(&engine, "balance x"),
+            Reply::Invalid { message } if message.starts_with("invalid client")
+        );
+        assert_matches!(
+            handle(&engine, "balance 70000"),
+            Reply::Invalid { message } if message.starts_with("invalid client")
+        );
+        assert_matches!(
+            handle(&engine, "balance 1 us-d"),
+            Reply::Invalid { message } if message.starts_with("invalid currency")
+        );
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let dir = state_dir("checkpoint");
+
+        let engine = Mutex::new(Engine::Journaled {
+            analysis: JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap(),
+            checkpoint_every: 2,
+        });
+        let journaled = |engine: &Mutex<Engine>| match &*engine.lock().unwrap() {
+            Engine::Journaled { analysis, .. } => analysis.journaled_events(),
+            Engine::InMemory(_) => unreachable!(),
+        };
+
+        // Periodic checkpoint
+        for tx in 1..=3 {
+            assert_matches!(handle(&engine, &deposit(tx)), Reply::Accepted);
+        }
+        assert_eq!(journaled(&engine), 1);
+
+        // Checkpoint on demand
+        assert_matches!(handle(&engine, "checkpoint"), Reply::Checkpointed);
+        assert_eq!(journaled(&engine), 0);
+
+        assert_matches!(handle(&engine, &deposit(4)), Reply::Accepted);
+        let expected = engine.lock().unwrap().analysis().clone();
+        drop(engine);
+
+        // Restart replays only the events journaled after the last checkpoint
+        let analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+        assert_eq!(analysis.analysis(), &expected);
+        assert_eq!(analysis.journaled_events(), 1);
+        assert_eq!(
+            analysis
+                .analysis()
+                .account_summary(Client(1), None)
+                .unwrap()
+                .available,
+            Amount::from(4)
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint_in_memory() {
+        let engine = engine();
+
+        assert_matches!(
+            handle(&engine, "checkpoint"),
+            Reply::Invalid { message } if message.contains("--state-dir")
+        );
+        assert_matches!(handle(&engine, "shutdown"), Reply::ShuttingDown);
+    }
+
+    #[test]
+    fn test_invalid_event() {
+        let engine = engine();
+
+        assert_matches!(
+            handle(&engine, "not an event"),
+            Reply::Invalid { message } if message.starts_with("invalid event")
+        );
+        assert_matches!(
+            handle(&engine, r#"{"type":"deposit","client":1,"tx":1}"#),
+            Reply::Invalid { message } if message.starts_with("invalid event")
+        );
+        assert_matches!(
+            handle(&engine, r#"{"type":"deposit","client":1,"tx":1,"amount":"NaN"}"#),
+            Reply::Invalid { message } if message.starts_with("invalid event")
+        );
+
+        assert!(engine
+            .lock()
+            .unwrap()
+            .analysis()
+            .account_summary(Client(1), None)
+            .is_none());
+    }
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+use crate::event::wrappers::Amount;
+
+/// Balance of client's account in a single [currency][crate::event::wrappers::Currency]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Balance {
+    /// Available amount
+    pub available: Amount,
+    /// Held amount (due to disputes in progress)
+    pub held: Amount,
+}
+
+impl Balance {
+    /// Total amount (available + held). \
+    /// [Analysis][crate::Analysis] guarantees that total amount does not overflow
+    pub fn total(&self) -> Amount {
+        self.available + self.held
+    }
+
+    /// Sets new amounts. Arithmetic producing the amounts is expected
+    /// to be checked, so `None` means that an overflow has occured.
+    /// Amounts are also rejected if their total does not fit into [Amount]. \
+    /// **Balance is left untouched in case of failure**
+    pub(in crate::analysis) fn update(
+        &mut self,
+        available: Option<Amount>,
+        held: Option<Amount>,
+    ) -> Option<()> {
+        let available = available?;
+        let held = held?;
+
+        available.checked_add(held)?;
+
+        self.available = available;
+        self.held = held;
+
+        Some(())
+    }
+}
@@ -1,17 +1,32 @@
 mod summary;
 pub use summary::Summary as AccountSummary;
+mod view;
+pub use view::{AccountView, OperationView};
+mod balance;
+pub use balance::Balance as AccountBalance;
 pub(super) mod operation;
 pub(super) use operation::Operation;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::event::wrappers::{Amount, Tx};
+use crate::event::wrappers::{Currency, Id, Tx};
 
 /// Represents client's account state during analysis
-#[derive(Debug, Clone, Default, PartialEq)]
-pub(super) struct Account {
-    pub(super) available_amount: Amount,
-    pub(super) held_amount: Amount,
-    /// Tracks client's operations
-    pub(super) operations: HashMap<Tx, Operation>,
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Account<T: Id = u32> {
+    /// Tracks client's balances by currency.
+    /// Events without a currency relate to the `None` one
+    pub(super) balances: BTreeMap<Option<Currency>, AccountBalance>,
+    /// Tracks client's operations in all currencies
+    pub(super) operations: HashMap<Tx<T>, Operation>,
+}
+
+// Not derived as transaction IDs are not required to have a default value
+impl<T: Id> Default for Account<T> {
+    fn default() -> Self {
+        Self {
+            balances: BTreeMap::new(),
+            operations: HashMap::new(),
+        }
+    }
 }
@@ -1,16 +1,43 @@
-use crate::event::wrappers::Amount;
+use serde::{Deserialize, Serialize};
+
+use crate::event::wrappers::{Amount, Currency};
 
 /// Only deposits and withdrawals count as operations.
 /// All actions related to dispute is something different.
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub(in crate::analysis) enum Kind {
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// Operation created by [deposit][crate::Event::Deposit]
     Deposit,
+    /// Operation created by [withdrawal][crate::Event::Withdrawal]
     Withdrawal,
 }
 
+/// Dispute lifecycle state of an operation
+///
+/// ```text
+/// Normal -> Disputed -> Resolved -> (Disputed again if re-disputes are allowed)
+///                    -> ChargedBack (final)
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    /// Operation has never been disputed
+    #[default]
+    Normal,
+    /// Operation is under dispute
+    Disputed,
+    /// Last dispute on the operation has been resolved
+    Resolved,
+    /// Operation has been charged back
+    ChargedBack,
+}
+
 /// Client's operation
 #[derive(Debug, Clone, PartialEq)]
 pub(in crate::analysis) struct Operation {
     pub kind: Kind,
     pub amount: Amount,
+    pub currency: Option<Currency>,
+    pub state: State,
 }
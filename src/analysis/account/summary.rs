@@ -1,14 +1,8 @@
 use serde::{Deserialize, Serialize};
 
-use crate::event::wrappers::Client;
+use crate::event::wrappers::{Amount, Client, Currency};
 
-/// Represent resulting account state \
-/// **Note that amounts are represented as strings.
-/// This is due to the requirement of max precision
-/// of 4 decimal places in resulting amounts which is
-/// easily achievable by opting out of floats in favor of strings**
-/// ([amount][crate::event::wrappers::Amount] renders fixed 4 decimal places in its
-/// [std::fmt::Display] implementation)
+/// Represent resulting account state in a single currency
 ///
 /// # Example
 /// ```
@@ -19,32 +13,47 @@ use crate::event::wrappers::Client;
 ///
 /// let mut analysis = Analysis::begin();
 ///
-/// let event = Event::Deposit { client: Client(1), tx: Tx(1), amount: Amount(1.0) };
+/// let event = Event::Deposit {
+///     client: Client(1),
+///     tx: Tx(1),
+///     amount: Amount::from(1),
+///     currency: None,
+/// };
 /// analysis.process_event(&event);
 ///
-/// let summary = analysis.summary();
+/// let summary = analysis.summary().collect::<Vec<AccountSummary>>();
 ///
 /// assert_eq!(
-///     summary.collect::<Vec<AccountSummary>>(),
+///     summary,
 ///     vec![AccountSummary {
 ///         client: Client(1),
+///         currency: None,
 ///         locked: false,
-///         available: "1.0000".to_string(),
-///         held: "0.0000".to_string(),
-///         total: "1.0000".to_string(),
+///         available: Amount::from(1),
+///         held: Amount::from(0),
 ///     }]
 /// );
+/// assert_eq!(summary[0].total(), Amount::from(1));
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct Summary {
+pub struct Summary<C = u16> {
     /// Client ID
-    pub client: Client,
+    pub client: Client<C>,
+    /// Currency of the balance. `None` for events without a currency
+    pub currency: Option<Currency>,
     /// Available amount
-    pub available: String,
+    pub available: Amount,
     /// Held amount (due to disputes in progress)
-    pub held: String,
-    /// Total amount (available + held)
-    pub total: String,
-    /// Whether account is locked (due to a chargeback)
+    pub held: Amount,
+    /// Whether account is locked (due to a chargeback).
+    /// Locks apply to all client's currencies
     pub locked: bool,
 }
+
+impl<C> Summary<C> {
+    /// Total amount (available + held). \
+    /// [Analysis][crate::Analysis] guarantees that total amount does not overflow
+    pub fn total(&self) -> Amount {
+        self.available + self.held
+    }
+}
@@ -0,0 +1,171 @@
+use super::{
+    operation::{Kind, State},
+    Account, AccountBalance, AccountSummary, Operation,
+};
+use crate::event::wrappers::{Amount, Client, Currency, Id, Tx};
+
+/// Read-only view of client's account borrowed from [Analysis][crate::Analysis].
+///
+/// Account holds a [balance][AccountBalance] per currency. [AccountView::available],
+/// [AccountView::held] and [AccountView::total] are the ones of the balance without a currency
+///
+/// # Example
+/// ```
+/// use scoring::{
+///     event::wrappers::{Amount, Client, Tx},
+///     Analysis, Event,
+/// };
+///
+/// let mut analysis = Analysis::begin();
+///
+/// let usd = "USD".parse().unwrap();
+/// let events = [
+///     Event::Deposit { client: Client(1), tx: Tx(1), amount: Amount::from(1), currency: None },
+///     Event::Deposit { client: Client(1), tx: Tx(2), amount: Amount::from(2), currency: Some(usd) },
+/// ];
+/// for event in events.iter() {
+///     analysis.process_event(event).unwrap();
+/// }
+///
+/// let account = analysis.account(Client(1)).unwrap();
+/// assert_eq!(account.available(), Amount::from(1));
+/// assert_eq!(account.balance(Some(usd)).unwrap().available, Amount::from(2));
+/// assert_eq!(account.balances().count(), 2);
+/// assert_eq!(account.operations().count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AccountView<'a, C = u16, T: Id = u32> {
+    #[doc(hidden)]
+    client: Client<C>,
+    #[doc(hidden)]
+    account: &'a Account<T>,
+    #[doc(hidden)]
+    locked: bool,
+}
+
+impl<'a, C: Id, T: Id> AccountView<'a, C, T> {
+    #[doc(hidden)]
+    pub(in crate::analysis) fn new(
+        client: Client<C>,
+        account: &'a Account<T>,
+        locked: bool,
+    ) -> Self {
+        Self {
+            client,
+            account,
+            locked,
+        }
+    }
+
+    /// Client ID
+    pub fn client(&self) -> Client<C> {
+        self.client.clone()
+    }
+
+    /// Available amount of the balance without a currency (zero if there is none)
+    pub fn available(&self) -> Amount {
+        self.balance(None).unwrap_or_default().available
+    }
+
+    /// Held amount (due to disputes in progress) of the balance without a currency
+    pub fn held(&self) -> Amount {
+        self.balance(None).unwrap_or_default().held
+    }
+
+    /// Total amount (available + held) of the balance without a currency
+    pub fn total(&self) -> Amount {
+        self.balance(None).unwrap_or_default().total()
+    }
+
+    /// Returns [balance][AccountBalance] in specified currency (if there is one)
+    pub fn balance(&self, currency: Option<Currency>) -> Option<AccountBalance> {
+        self.account.balances.get(&currency).copied()
+    }
+
+    /// Iterates over balances ordered by currency.
+    /// Balance without a currency (if any) goes first
+    pub fn balances(&self) -> impl Iterator<Item = (Option<Currency>, AccountBalance)> + 'a {
+        self.account
+            .balances
+            .iter()
+            .map(|(currency, balance)| (*currency, *balance))
+    }
+
+    /// Whether account is locked
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Returns client's operation by [transaction ID][Tx]
+    pub fn operation(&self, tx: Tx<T>) -> Option<OperationView<T>> {
+        self.account
+            .operations
+            .get(&tx)
+            .map(|operation| OperationView::new(tx, operation))
+    }
+
+    /// Iterates over client's operations in arbitrary order
+    pub fn operations(&self) -> impl Iterator<Item = OperationView<T>> + 'a {
+        self.account
+            .operations
+            .iter()
+            .map(|(tx, operation)| OperationView::new(tx.clone(), operation))
+    }
+
+    /// Returns [summary][AccountSummary] of the balance in specified currency (if there is one)
+    pub fn summary(&self, currency: Option<Currency>) -> Option<AccountSummary<C>> {
+        self.balance(currency)
+            .map(|balance| self.balance_summary(currency, balance))
+    }
+
+    /// Iterates over [summaries][AccountSummary] of all balances ordered by currency
+    pub fn summaries(&self) -> impl Iterator<Item = AccountSummary<C>> + 'a {
+        let view = self.clone();
+
+        self.balances()
+            .map(move |(currency, balance)| view.balance_summary(currency, balance))
+    }
+
+    #[doc(hidden)]
+    fn balance_summary(
+        &self,
+        currency: Option<Currency>,
+        balance: AccountBalance,
+    ) -> AccountSummary<C> {
+        AccountSummary {
+            client: self.client.clone(),
+            currency,
+            available: balance.available,
+            held: balance.held,
+            locked: self.locked,
+        }
+    }
+}
+
+/// Client's operation (deposit or withdrawal) as seen by [Analysis][crate::Analysis]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperationView<T = u32> {
+    /// Transaction ID
+    pub tx: Tx<T>,
+    /// Kind of the operation
+    pub kind: Kind,
+    /// Amount of the operation
+    pub amount: Amount,
+    /// Currency of the operation
+    pub currency: Option<Currency>,
+    /// Dispute lifecycle state of the operation
+    pub state: State,
+}
+
+impl<T> OperationView<T> {
+    #[doc(hidden)]
+    fn new(tx: Tx<T>, operation: &Operation) -> Self {
+        Self {
+            tx,
+            kind: operation.kind,
+            amount: operation.amount,
+            currency: operation.currency,
+            state: operation.state,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use super::{LockedAccountPolicy, TxUniqueness, WithdrawalDisputePolicy};
+
+/// Set of business rules [Analysis][super::Analysis] follows.
+/// Default configuration is the rulebook described in README
+///
+/// Configuration is built by chaining setters on top of the default one:
+/// ```
+/// use scoring::{Analysis, AnalysisConfig, TxUniqueness};
+///
+/// let config = AnalysisConfig::default()
+///     .allow_zero_amounts(false)
+///     .tx_uniqueness(TxUniqueness::PerClient);
+///
+/// let analysis = Analysis::with_config(config);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AnalysisConfig {
+    /// Whether deposits and withdrawals of zero [amount][crate::event::wrappers::Amount]
+    /// are accepted (default: `true`)
+    pub allow_zero_amounts: bool,
+    /// Whether deposits and withdrawals of negative [amount][crate::event::wrappers::Amount]
+    /// are rejected (default: `true`)
+    pub reject_negative_amounts: bool,
+    /// Whether chargeback of a deposit locks the account (default: `true`)
+    pub lock_on_chargeback: bool,
+    /// Events accepted for locked accounts (default: [LockedAccountPolicy::RejectAll])
+    pub locked_account_policy: LockedAccountPolicy,
+    /// Scope of transaction ID uniqueness (default: [TxUniqueness::Global])
+    pub tx_uniqueness: TxUniqueness,
+    /// Whether withdrawal requires the account to exist beforehand (default: `true`). \
+    /// **Note that withdrawal still can't exceed available funds**
+    pub require_account_for_withdrawal: bool,
+    /// Handling of withdrawal disputes (default: [WithdrawalDisputePolicy::Reject])
+    pub withdrawal_dispute_policy: WithdrawalDisputePolicy,
+    /// Whether an operation can be disputed again after its dispute
+    /// has been resolved (default: `true`). \
+    /// **Note that charged back operations can never be disputed again**
+    pub allow_redisputes: bool,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            allow_zero_amounts: true,
+            reject_negative_amounts: true,
+            lock_on_chargeback: true,
+            locked_account_policy: LockedAccountPolicy::default(),
+            tx_uniqueness: TxUniqueness::default(),
+            require_account_for_withdrawal: true,
+            withdrawal_dispute_policy: WithdrawalDisputePolicy::default(),
+            allow_redisputes: true,
+        }
+    }
+}
+
+impl AnalysisConfig {
+    /// Sets [AnalysisConfig::allow_zero_amounts]
+    pub fn allow_zero_amounts(mut self, allow: bool) -> Self {
+        self.allow_zero_amounts = allow;
+        self
+    }
+
+    /// Sets [AnalysisConfig::reject_negative_amounts]
+    pub fn reject_negative_amounts(mut self, reject: bool) -> Self {
+        self.reject_negative_amounts = reject;
+        self
+    }
+
+    /// Sets [AnalysisConfig::lock_on_chargeback]
+    pub fn lock_on_chargeback(mut self, lock: bool) -> Self {
+        self.lock_on_chargeback = lock;
+        self
+    }
+
+    /// Sets [AnalysisConfig::locked_account_policy]
+    pub fn locked_account_policy(mut self, policy: LockedAccountPolicy) -> Self {
+        self.locked_account_policy = policy;
+        self
+    }
+
+    /// Sets [AnalysisConfig::tx_uniqueness]
+    pub fn tx_uniqueness(mut self, uniqueness: TxUniqueness) -> Self {
+        self.tx_uniqueness = uniqueness;
+        self
+    }
+
+    /// Sets [AnalysisConfig::require_account_for_withdrawal]
+    pub fn require_account_for_withdrawal(mut self, require: bool) -> Self {
+        self.require_account_for_withdrawal = require;
+        self
+    }
+
+    /// Sets [AnalysisConfig::withdrawal_dispute_policy]
+    pub fn withdrawal_dispute_policy(mut self, policy: WithdrawalDisputePolicy) -> Self {
+        self.withdrawal_dispute_policy = policy;
+        self
+    }
+
+    /// Sets [AnalysisConfig::allow_redisputes]
+    pub fn allow_redisputes(mut self, allow: bool) -> Self {
+        self.allow_redisputes = allow;
+        self
+    }
+}
@@ -1,47 +1,357 @@
-use crate::event::wrappers::{Amount, Client, Tx};
+use serde::{Serialize, Serializer};
+
+use crate::event::wrappers::{Amount, Client, Id, Tx};
 
 /// Represents business-errors which can occur during [Analysis][super::Analysis]
+///
+/// Every error has a stable [code][Error::code] and a [severity][Error::severity]. \
+/// Errors serialize into a flat structure holding code, severity, human-readable
+/// message and involved [client ID][Client], [transaction ID][Tx] and [amount][Amount]
+/// (whichever are applicable):
+/// ```
+/// use scoring::{
+///     event::wrappers::{Amount, Client, Tx},
+///     AnalysisError,
+/// };
+///
+/// let err = AnalysisError::InsufficientFunds(Client(1), Tx(2), Amount::from(3));
+///
+/// assert_eq!(
+///     serde_json::to_string(&err).unwrap(),
+///     r#"{"code":"E_INSUFFICIENT_FUNDS","severity":"client_mistake","message":"insufficient funds (client: 1, tx: 2)","client":1,"tx":2,"amount":"3.0000"}"#
+/// );
+/// ```
 #[derive(Debug, PartialEq, thiserror::Error)]
-pub enum Error {
+pub enum Error<C = u16, T = u32> {
     /// Attempt to [deposit][super::Event::Deposit] or
-    /// [withdraw][super::Event::Withdrawal] negative [amount][Amount] \
-    /// **Note that 0 amount for both types of transaction is allowed**
+    /// [withdraw][super::Event::Withdrawal] negative [amount][Amount]
+    /// when they are [rejected][super::AnalysisConfig::reject_negative_amounts] (the default). \
+    /// **Note that 0 amount is a different case:** it is accepted by default
+    /// and rejected with [ZeroAmountOperation][Error::ZeroAmountOperation] when it is not
+    /// [allowed][super::AnalysisConfig::allow_zero_amounts]
+    ///
+    /// Code: `E_NEGATIVE_AMOUNT`, severity: [DataCorruption][Severity::DataCorruption]
     #[error("negative amount operation (client: {0}, tx: {1}, amount: {2})")]
-    NegativeAmountOperation(Client, Tx, Amount),
+    NegativeAmountOperation(Client<C>, Tx<T>, Amount),
 
     /// [Transaction ID][Tx] occured more than once during [Analysis][super::Analysis]
+    ///
+    /// Code: `E_DUPLICATE_OPERATION`, severity: [DataCorruption][Severity::DataCorruption]
     #[error("duplicate operation (tx: {0})")]
-    DuplicateOperation(Tx),
+    DuplicateOperation(Tx<T>),
 
     /// Specified [client ID][Client] not found
+    ///
+    /// Code: `E_ACCOUNT_NOT_FOUND`, severity: [ClientMistake][Severity::ClientMistake]
     #[error("account not found (client: {0})")]
-    AccountNotFound(Client),
+    AccountNotFound(Client<C>),
 
     /// Attempt to [withdraw][super::Event::Withdrawal] when transaction amount
     /// exceeds client's available funds
+    ///
+    /// Code: `E_INSUFFICIENT_FUNDS`, severity: [ClientMistake][Severity::ClientMistake]
     #[error("insufficient funds (client: {0}, tx: {1})")]
-    InsufficientFunds(Client, Tx, Amount),
+    InsufficientFunds(Client<C>, Tx<T>, Amount),
 
     /// Attempt to [initiate dispute][super::Event::Dispute] on a
     /// transaction which is already under dispute
+    ///
+    /// Code: `E_DISPUTE_ALREADY_IN_PROGRESS`, severity: [ClientMistake][Severity::ClientMistake]
     #[error("dispute already in progress (tx: {0})")]
-    DisputeAlreadyInProgress(Tx),
+    DisputeAlreadyInProgress(Tx<T>),
 
     /// [Transaction ID][Tx] is not found among [client's][Client] transaction
+    ///
+    /// Code: `E_OPERATION_NOT_FOUND`, severity: [ClientMistake][Severity::ClientMistake]
     #[error("operation not found (client: {0}, tx: {1})")]
-    OperationNotFound(Client, Tx),
+    OperationNotFound(Client<C>, Tx<T>),
 
     /// Attempt to [initiate dispute][super::Event::Dispute] on a
     /// [withdrawal][super::Event::Withdrawal] transaction
+    ///
+    /// Code: `E_WITHDRAWAL_DISPUTE`, severity: [PolicyRejection][Severity::PolicyRejection]
     #[error("withdrawal dispute attempt (client: {0}, tx: {1})")]
-    WithdrawalDisputeAttempt(Client, Tx),
+    WithdrawalDisputeAttempt(Client<C>, Tx<T>),
 
     /// Dispute not found when attempting to [resolve][super::Event::Resolve] or
     /// [chargeback][super::Event::Chargeback]
+    ///
+    /// Code: `E_DISPUTE_NOT_FOUND`, severity: [ClientMistake][Severity::ClientMistake]
     #[error("dispute not found (client: {0}, tx: {1})")]
-    DisputeNotFound(Client, Tx),
+    DisputeNotFound(Client<C>, Tx<T>),
 
     /// Operation can't be performed because account is locked
+    ///
+    /// Code: `E_ACCOUNT_LOCKED`, severity: [PolicyRejection][Severity::PolicyRejection]
     #[error("account locked (client: {0})")]
-    AccountLocked(Client),
+    AccountLocked(Client<C>),
+
+    /// Attempt to [deposit][super::Event::Deposit] or
+    /// [withdraw][super::Event::Withdrawal] zero [amount][Amount]
+    /// when it is not [allowed][super::AnalysisConfig::allow_zero_amounts]
+    ///
+    /// Code: `E_ZERO_AMOUNT`, severity: [PolicyRejection][Severity::PolicyRejection]
+    #[error("zero amount operation (client: {0}, tx: {1})")]
+    ZeroAmountOperation(Client<C>, Tx<T>),
+
+    /// Attempt to [freeze][super::Event::Freeze] an account which is already locked
+    ///
+    /// Code: `E_ACCOUNT_ALREADY_LOCKED`, severity: [ClientMistake][Severity::ClientMistake]
+    #[error("account already locked (client: {0})")]
+    AccountAlreadyLocked(Client<C>),
+
+    /// Attempt to [unlock][super::Event::Unlock] an account which is not locked
+    ///
+    /// Code: `E_ACCOUNT_NOT_LOCKED`, severity: [ClientMistake][Severity::ClientMistake]
+    #[error("account not locked (client: {0})")]
+    AccountNotLocked(Client<C>),
+
+    /// Attempt to [initiate dispute][super::Event::Dispute] on a transaction
+    /// which has already been disputed and resolved when re-disputes are not
+    /// [allowed][super::AnalysisConfig::allow_redisputes]
+    ///
+    /// Code: `E_REDISPUTE_NOT_ALLOWED`, severity: [PolicyRejection][Severity::PolicyRejection]
+    #[error("redispute not allowed (client: {0}, tx: {1})")]
+    RedisputeNotAllowed(Client<C>, Tx<T>),
+
+    /// Attempt to [resolve][super::Event::Resolve] or
+    /// [chargeback][super::Event::Chargeback] a dispute which has already been resolved
+    ///
+    /// Code: `E_DISPUTE_ALREADY_RESOLVED`, severity: [ClientMistake][Severity::ClientMistake]
+    #[error("dispute already resolved (client: {0}, tx: {1})")]
+    DisputeAlreadyResolved(Client<C>, Tx<T>),
+
+    /// Attempt to dispute, resolve or chargeback a transaction
+    /// which has already been charged back
+    ///
+    /// Code: `E_OPERATION_CHARGED_BACK`, severity: [ClientMistake][Severity::ClientMistake]
+    #[error("operation charged back (client: {0}, tx: {1})")]
+    OperationChargedBack(Client<C>, Tx<T>),
+
+    /// Operation would overflow client's available, held or total [amount][Amount]
+    ///
+    /// Code: `E_AMOUNT_OVERFLOW`, severity: [DataCorruption][Severity::DataCorruption]
+    #[error("amount overflow (client: {0}, tx: {1})")]
+    AmountOverflow(Client<C>, Tx<T>),
+
+    /// Attempt to dispute, resolve or chargeback a transaction
+    /// in a [currency][crate::event::wrappers::Currency] other than the one of the transaction
+    ///
+    /// Code: `E_CURRENCY_MISMATCH`, severity: [ClientMistake][Severity::ClientMistake]
+    #[error("currency mismatch (client: {0}, tx: {1})")]
+    CurrencyMismatch(Client<C>, Tx<T>),
+}
+
+/// Class of an [error][Error]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Event is invalid with regard to the client's current state
+    /// (e.g. not enough funds or unknown transaction)
+    ClientMistake,
+    /// Event log is inconsistent (e.g. reused transaction ID).
+    /// Such errors hint at problems upstream
+    DataCorruption,
+    /// Event is valid by itself but is rejected by business rules
+    /// (e.g. account is locked)
+    PolicyRejection,
+}
+
+impl<C: Id, T: Id> Error<C, T> {
+    /// Stable machine-readable error code
+    ///
+    /// | Code                            | Error                                 |
+    /// |---------------------------------|---------------------------------------|
+    /// | `E_NEGATIVE_AMOUNT` | [NegativeAmountOperation][Error::NegativeAmountOperation] |
+    /// | `E_DUPLICATE_OPERATION` | [DuplicateOperation][Error::DuplicateOperation] |
+    /// | `E_ACCOUNT_NOT_FOUND` | [AccountNotFound][Error::AccountNotFound] |
+    /// | `E_INSUFFICIENT_FUNDS` | [InsufficientFunds][Error::InsufficientFunds] |
+    /// | `E_DISPUTE_ALREADY_IN_PROGRESS` | [DisputeAlreadyInProgress][Error::DisputeAlreadyInProgress] |
+    /// | `E_OPERATION_NOT_FOUND` | [OperationNotFound][Error::OperationNotFound] |
+    /// | `E_WITHDRAWAL_DISPUTE` | [WithdrawalDisputeAttempt][Error::WithdrawalDisputeAttempt] |
+    /// | `E_DISPUTE_NOT_FOUND` | [DisputeNotFound][Error::DisputeNotFound] |
+    /// | `E_ACCOUNT_LOCKED` | [AccountLocked][Error::AccountLocked] |
+    /// | `E_ZERO_AMOUNT` | [ZeroAmountOperation][Error::ZeroAmountOperation] |
+    /// | `E_ACCOUNT_ALREADY_LOCKED` | [AccountAlreadyLocked][Error::AccountAlreadyLocked] |
+    /// | `E_ACCOUNT_NOT_LOCKED` | [AccountNotLocked][Error::AccountNotLocked] |
+    /// | `E_REDISPUTE_NOT_ALLOWED` | [RedisputeNotAllowed][Error::RedisputeNotAllowed] |
+    /// | `E_DISPUTE_ALREADY_RESOLVED` | [DisputeAlreadyResolved][Error::DisputeAlreadyResolved] |
+    /// | `E_OPERATION_CHARGED_BACK` | [OperationChargedBack][Error::OperationChargedBack] |
+    /// | `E_AMOUNT_OVERFLOW` | [AmountOverflow][Error::AmountOverflow] |
+    /// | `E_CURRENCY_MISMATCH` | [CurrencyMismatch][Error::CurrencyMismatch] |
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NegativeAmountOperation(..) => "E_NEGATIVE_AMOUNT",
+            Self::DuplicateOperation(..) => "E_DUPLICATE_OPERATION",
+            Self::AccountNotFound(..) => "E_ACCOUNT_NOT_FOUND",
+            Self::InsufficientFunds(..) => "E_INSUFFICIENT_FUNDS",
+            Self::DisputeAlreadyInProgress(..) => "E_DISPUTE_ALREADY_IN_PROGRESS",
+            Self::OperationNotFound(..) => "E_OPERATION_NOT_FOUND",
+            Self::WithdrawalDisputeAttempt(..) => "E_WITHDRAWAL_DISPUTE",
+            Self::DisputeNotFound(..) => "E_DISPUTE_NOT_FOUND",
+            Self::AccountLocked(..) => "E_ACCOUNT_LOCKED",
+            Self::ZeroAmountOperation(..) => "E_ZERO_AMOUNT",
+            Self::AccountAlreadyLocked(..) => "E_ACCOUNT_ALREADY_LOCKED",
+            Self::AccountNotLocked(..) => "E_ACCOUNT_NOT_LOCKED",
+            Self::RedisputeNotAllowed(..) => "E_REDISPUTE_NOT_ALLOWED",
+            Self::DisputeAlreadyResolved(..) => "E_DISPUTE_ALREADY_RESOLVED",
+            Self::OperationChargedBack(..) => "E_OPERATION_CHARGED_BACK",
+            Self::AmountOverflow(..) => "E_AMOUNT_OVERFLOW",
+            Self::CurrencyMismatch(..) => "E_CURRENCY_MISMATCH",
+        }
+    }
+
+    /// [Severity] class of the error
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::NegativeAmountOperation(..) => Severity::DataCorruption,
+            Self::DuplicateOperation(..) => Severity::DataCorruption,
+            Self::AccountNotFound(..) => Severity::ClientMistake,
+            Self::InsufficientFunds(..) => Severity::ClientMistake,
+            Self::DisputeAlreadyInProgress(..) => Severity::ClientMistake,
+            Self::OperationNotFound(..) => Severity::ClientMistake,
+            Self::WithdrawalDisputeAttempt(..) => Severity::PolicyRejection,
+            Self::DisputeNotFound(..) => Severity::ClientMistake,
+            Self::AccountLocked(..) => Severity::PolicyRejection,
+            Self::ZeroAmountOperation(..) => Severity::PolicyRejection,
+            Self::AccountAlreadyLocked(..) => Severity::ClientMistake,
+            Self::AccountNotLocked(..) => Severity::ClientMistake,
+            Self::RedisputeNotAllowed(..) => Severity::PolicyRejection,
+            Self::DisputeAlreadyResolved(..) => Severity::ClientMistake,
+            Self::OperationChargedBack(..) => Severity::ClientMistake,
+            Self::AmountOverflow(..) => Severity::DataCorruption,
+            Self::CurrencyMismatch(..) => Severity::ClientMistake,
+        }
+    }
+
+    /// [Client ID][Client] involved (if known)
+    pub fn client(&self) -> Option<&Client<C>> {
+        match self {
+            Self::NegativeAmountOperation(client, ..)
+            | Self::AccountNotFound(client)
+            | Self::InsufficientFunds(client, ..)
+            | Self::OperationNotFound(client, ..)
+            | Self::WithdrawalDisputeAttempt(client, ..)
+            | Self::DisputeNotFound(client, ..)
+            | Self::AccountLocked(client)
+            | Self::ZeroAmountOperation(client, ..)
+            | Self::AccountAlreadyLocked(client)
+            | Self::AccountNotLocked(client)
+            | Self::RedisputeNotAllowed(client, ..)
+            | Self::DisputeAlreadyResolved(client, ..)
+            | Self::OperationChargedBack(client, ..)
+            | Self::AmountOverflow(client, ..)
+            | Self::CurrencyMismatch(client, ..) => Some(client),
+            Self::DuplicateOperation(..) | Self::DisputeAlreadyInProgress(..) => None,
+        }
+    }
+
+    /// [Transaction ID][Tx] involved (if known)
+    pub fn tx(&self) -> Option<&Tx<T>> {
+        match self {
+            Self::NegativeAmountOperation(_, tx, _)
+            | Self::DuplicateOperation(tx)
+            | Self::InsufficientFunds(_, tx, _)
+            | Self::DisputeAlreadyInProgress(tx)
+            | Self::OperationNotFound(_, tx)
+            | Self::WithdrawalDisputeAttempt(_, tx)
+            | Self::DisputeNotFound(_, tx)
+            | Self::ZeroAmountOperation(_, tx)
+            | Self::RedisputeNotAllowed(_, tx)
+            | Self::DisputeAlreadyResolved(_, tx)
+            | Self::OperationChargedBack(_, tx)
+            | Self::AmountOverflow(_, tx)
+            | Self::CurrencyMismatch(_, tx) => Some(tx),
+            Self::AccountNotFound(..)
+            | Self::AccountLocked(..)
+            | Self::AccountAlreadyLocked(..)
+            | Self::AccountNotLocked(..) => None,
+        }
+    }
+
+    /// [Amount] involved (if known)
+    pub fn amount(&self) -> Option<Amount> {
+        match self {
+            Self::NegativeAmountOperation(_, _, amount) | Self::InsufficientFunds(_, _, amount) => {
+                Some(*amount)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<C: Id, T: Id> Serialize for Error<C, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Repr<'a, C, T> {
+            code: &'static str,
+            severity: Severity,
+            message: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            client: Option<&'a Client<C>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            tx: Option<&'a Tx<T>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            amount: Option<Amount>,
+        }
+
+        Repr {
+            code: self.code(),
+            severity: self.severity(),
+            message: self.to_string(),
+            client: self.client(),
+            tx: self.tx(),
+            amount: self.amount(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_serialize() {
+        let err: Error = Error::DuplicateOperation(Tx(1));
+
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({
+                "code": "E_DUPLICATE_OPERATION",
+                "severity": "data_corruption",
+                "message": "duplicate operation (tx: 1)",
+                "tx": 1,
+            })
+        );
+
+        let err: Error = Error::AccountLocked(Client(1));
+
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({
+                "code": "E_ACCOUNT_LOCKED",
+                "severity": "policy_rejection",
+                "message": "account locked (client: 1)",
+                "client": 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_accessors() {
+        let err: Error = Error::InsufficientFunds(Client(1), Tx(2), Amount::from(3));
+
+        assert_eq!(err.client(), Some(&Client(1)));
+        assert_eq!(err.tx(), Some(&Tx(2)));
+        assert_eq!(err.amount(), Some(Amount::from(3)));
+
+        let err: Error = Error::DuplicateOperation(Tx(1));
+
+        assert_eq!(err.client(), None);
+        assert_eq!(err.tx(), Some(&Tx(1)));
+        assert_eq!(err.amount(), None);
+    }
 }
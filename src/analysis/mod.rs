@@ -2,24 +2,42 @@
 mod tests;
 
 mod account;
-pub use account::AccountSummary;
 use account::{operation, Account, Operation};
+pub use account::{
+    operation::{Kind as OperationKind, State as OperationState},
+    AccountBalance, AccountSummary, AccountView, OperationView,
+};
 
 /// provides [AnalysisSummary]
 mod summary;
-pub use summary::AnalysisSummary;
+pub use summary::{AnalysisSummary, SummaryOrder};
+
+/// provides policies used in [AnalysisConfig]
+mod policy;
+pub use policy::{LockedAccountPolicy, TxUniqueness, WithdrawalDisputePolicy};
+
+/// provides [AnalysisConfig]
+mod config;
+pub use config::AnalysisConfig;
+
+/// provides [Analysis::snapshot] and [Analysis::restore]
+mod snapshot;
+pub use snapshot::Error as SnapshotError;
+
+/// provides savepoints which undo an event that could not be journaled
+mod savepoint;
 
 #[doc(hidden)]
 mod error;
-pub use error::Error as AnalysisError;
+pub use error::{Error as AnalysisError, Severity as AnalysisErrorSeverity};
 
 /// Utility error type which binds [AnalysisError] to [std::result::Result]
-pub type AnalysisResult<T> = Result<T, AnalysisError>;
+pub type AnalysisResult<R, C = u16, T = u32> = Result<R, AnalysisError<C, T>>;
 
 use std::collections::{hash_map::Entry, HashMap, HashSet};
 
-use crate::event::wrappers::{Amount, Client, Tx};
-use crate::event::Event;
+use crate::event::wrappers::{Amount, Client, Currency, Id, Tx};
+use crate::event::{Event, EventType};
 
 /// Reprsents internal state of analysis process.
 /// It's life cycle can be seen as follows:
@@ -36,323 +54,678 @@ use crate::event::Event;
 ///
 /// let symmary = analysis.summary();
 /// ```
-#[derive(Debug, Clone, PartialEq, Default)]
-pub struct Analysis {
+///
+/// Analysis is generic over the underlying types of [client][Client] and
+/// [transaction][Tx] IDs (see [Id]). [Analysis::begin] and [Analysis::with_config]
+/// use the default ones (`u16` and `u32`), [Analysis::new] accepts any:
+/// ```
+/// use scoring::{
+///     event::wrappers::{Amount, Client, Tx},
+///     Analysis, AnalysisConfig, Event,
+/// };
+///
+/// let mut analysis = Analysis::<String, u64>::new(AnalysisConfig::default());
+///
+/// let client = Client("merchant-1".to_string());
+/// let event = Event::Deposit {
+///     client: client.clone(),
+///     tx: Tx(1 << 40),
+///     amount: Amount::from(1),
+///     currency: None,
+/// };
+/// analysis.process_event(&event).unwrap();
+///
+/// assert_eq!(analysis.account(client).unwrap().available(), Amount::from(1));
+/// ```
+///
+/// Balances are tracked per client and [currency][Currency]. Disputes, resolves and
+/// chargebacks must specify the currency of the disputed operation.
+/// Accounts are locked as a whole, i.e. in all currencies
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis<C: Id = u16, T: Id = u32> {
     #[doc(hidden)]
-    // Tracks client accounts' states
-    accounts: HashMap<Client, Account>,
+    // Tracks client accounts' states (balances in all currencies and operations)
+    accounts: HashMap<Client<C>, Account<T>>,
     #[doc(hidden)]
     // Tracks active disputes
-    disputes: HashMap<Tx, Client>,
+    disputes: HashSet<(Client<C>, Tx<T>)>,
     #[doc(hidden)]
     // Tracks locked accounts
-    locked_accounts: HashSet<Client>,
+    locked_accounts: HashSet<Client<C>>,
     #[doc(hidden)]
     // Tracks used txs (transaction IDs). txs should be unique
-    used_txs: HashSet<Tx>,
+    used_txs: HashSet<Tx<T>>,
+    #[doc(hidden)]
+    config: AnalysisConfig,
+}
+
+// Not derived as IDs are not required to have a default value
+impl<C: Id, T: Id> Default for Analysis<C, T> {
+    fn default() -> Self {
+        Self::new(AnalysisConfig::default())
+    }
 }
 
 impl Analysis {
-    /// Initializes analysis
+    /// Initializes analysis with [default][AnalysisConfig::default] configuration
     pub fn begin() -> Self {
         Analysis::default()
     }
 
-    /// Turns [Analysis] into [AnalysisSummary]
-    pub fn summary(self) -> AnalysisSummary {
+    /// Initializes analysis with specified [configuration][AnalysisConfig]
+    pub fn with_config(config: AnalysisConfig) -> Self {
+        Analysis::new(config)
+    }
+}
+
+impl<C: Id, T: Id> Analysis<C, T> {
+    /// Initializes analysis with specified [configuration][AnalysisConfig]
+    /// and any [client][Client] and [transaction][Tx] ID types
+    pub fn new(config: AnalysisConfig) -> Self {
+        Self {
+            accounts: HashMap::new(),
+            disputes: HashSet::new(),
+            locked_accounts: HashSet::new(),
+            used_txs: HashSet::new(),
+            config,
+        }
+    }
+
+    /// Returns [configuration][AnalysisConfig] analysis follows
+    pub fn config(&self) -> &AnalysisConfig {
+        &self.config
+    }
+
+    /// Turns [Analysis] into [AnalysisSummary].
+    /// Accounts are ordered by [client ID][Client]
+    pub fn summary(self) -> AnalysisSummary<C> {
         AnalysisSummary::from(self)
     }
 
+    /// Turns [Analysis] into [AnalysisSummary] with accounts in specified [order][SummaryOrder]
+    pub fn summary_ordered(self, order: SummaryOrder) -> AnalysisSummary<C> {
+        AnalysisSummary::new(&self, order)
+    }
+
+    /// Produces [AnalysisSummary] of the current state with accounts
+    /// in specified [order][SummaryOrder]. Analysis can be continued afterwards
+    pub fn current_summary(&self, order: SummaryOrder) -> AnalysisSummary<C> {
+        AnalysisSummary::new(self, order)
+    }
+
+    /// Returns current [summary][AccountSummary] of a single client's balance
+    /// in specified currency (if it exists)
+    pub fn account_summary(
+        &self,
+        client: Client<C>,
+        currency: Option<Currency>,
+    ) -> Option<AccountSummary<C>> {
+        self.account(client)?.summary(currency)
+    }
+
+    /// Returns read-only [view][AccountView] of client's account (if it exists)
+    pub fn account(&self, client: Client<C>) -> Option<AccountView<'_, C, T>> {
+        let locked = self.locked_accounts.contains(&client);
+
+        self.accounts
+            .get(&client)
+            .map(|account| AccountView::new(client, account, locked))
+    }
+
+    /// Iterates over read-only [views][AccountView] of all accounts in arbitrary order
+    pub fn iter_accounts(&self) -> impl Iterator<Item = AccountView<'_, C, T>> {
+        self.accounts.iter().map(|(client, account)| {
+            let locked = self.locked_accounts.contains(client);
+
+            AccountView::new(client.clone(), account, locked)
+        })
+    }
+
+    /// Returns whether client's account is locked
+    pub fn is_locked(&self, client: Client<C>) -> bool {
+        self.locked_accounts.contains(&client)
+    }
+
+    /// Returns client's [operation][OperationView] by [transaction ID][Tx] (if it exists)
+    pub fn operation(&self, client: Client<C>, tx: Tx<T>) -> Option<OperationView<T>> {
+        self.account(client)?.operation(tx)
+    }
+
+    /// Iterates over disputes in progress in arbitrary order
+    pub fn open_disputes(&self) -> impl Iterator<Item = (Client<C>, Tx<T>)> + '_ {
+        self.disputes.iter().cloned()
+    }
+
     /// Processes single [event][Event]. Processing modifies [analysis][Analysis] internal state.
-    pub fn process_event(&mut self, event: &Event) -> AnalysisResult<()> {
+    pub fn process_event(&mut self, event: &Event<C, T>) -> AnalysisResult<(), C, T> {
         match event {
-            Event::Deposit { client, tx, amount } => self.process_deposit(*client, *tx, *amount),
-            Event::Withdrawal { client, tx, amount } => {
-                self.process_withdrawal(*client, *tx, *amount)
-            }
-            Event::Dispute { client, tx } => self.process_dispute_init(*client, *tx),
-            Event::Resolve { client, tx } => self.process_dispute_resolve(*client, *tx),
-            Event::Chargeback { client, tx } => self.process_dispute_chargeback(*client, *tx),
+            Event::Deposit {
+                client,
+                tx,
+                amount,
+                currency,
+            } => self.process_deposit(client.clone(), tx.clone(), *amount, *currency),
+            Event::Withdrawal {
+                client,
+                tx,
+                amount,
+                currency,
+            } => self.process_withdrawal(client.clone(), tx.clone(), *amount, *currency),
+            Event::Dispute {
+                client,
+                tx,
+                currency,
+            } => self.process_dispute_init(client.clone(), tx.clone(), *currency),
+            Event::Resolve {
+                client,
+                tx,
+                currency,
+            } => self.process_dispute_resolve(client.clone(), tx.clone(), *currency),
+            Event::Chargeback {
+                client,
+                tx,
+                currency,
+            } => self.process_dispute_chargeback(client.clone(), tx.clone(), *currency),
+            Event::Freeze { client, reason } => self.process_freeze(client.clone(), reason),
+            Event::Unlock { client, reason } => self.process_unlock(client.clone(), reason),
         }
     }
 
     #[doc(hidden)]
-    fn process_deposit(&mut self, client: Client, tx: Tx, amount: Amount) -> AnalysisResult<()> {
+    fn process_deposit(
+        &mut self,
+        client: Client<C>,
+        tx: Tx<T>,
+        amount: Amount,
+        currency: Option<Currency>,
+    ) -> AnalysisResult<(), C, T> {
         tracing::trace!(
-            "attempting deposit: (client: {}, tx: {}, amount: {})",
+            "attempting deposit: (client: {}, tx: {}, amount: {}, currency: {:?})",
             client,
             tx,
-            amount
+            amount,
+            currency
         );
 
-        if self.locked_accounts.contains(&client) {
-            return Err(AnalysisError::AccountLocked(client));
-        }
+        self.check_account_locked(&client, EventType::Deposit)?;
+
+        self.check_tx_unique(&tx)?;
+        self.check_amount(&client, &tx, amount)?;
+
+        let account = self.accounts.entry(client.clone()).or_default();
 
-        if self.used_txs.contains(&tx) {
+        // Transaction IDs might be unique only within client's operations
+        if account.operations.contains_key(&tx) {
             return Err(AnalysisError::DuplicateOperation(tx));
         }
 
-        if amount.is_negative() {
-            return Err(AnalysisError::NegativeAmountOperation(client, tx, amount));
-        }
+        let balance = account.balances.entry(currency).or_default();
 
-        let account = self.accounts.entry(client).or_default();
+        balance
+            .update(balance.available.checked_add(amount), Some(balance.held))
+            .ok_or_else(|| AnalysisError::AmountOverflow(client.clone(), tx.clone()))?;
 
-        match account.operations.entry(tx) {
-            Entry::Vacant(entry) => {
-                let operation = Operation {
-                    kind: operation::Kind::Deposit,
-                    amount,
-                };
+        let operation = Operation {
+            kind: operation::Kind::Deposit,
+            amount,
+            currency,
+            state: operation::State::Normal,
+        };
 
-                tracing::trace!(
-                    "deposit operation recorded: (client: {}, tx: {}, amount: {})",
-                    client,
-                    tx,
-                    amount
-                );
+        account.operations.insert(tx.clone(), operation);
 
-                entry.insert(operation);
-            }
-            // This branch shouldn't be reached
-            Entry::Occupied(_) => return Err(AnalysisError::DuplicateOperation(tx)),
-        }
-
-        account.available_amount += amount;
+        tracing::trace!(
+            "deposit operation recorded: (client: {}, tx: {}, amount: {})",
+            client,
+            tx,
+            amount
+        );
 
         tracing::trace!(
-            "available amount changed: (client: {}, amount: {}, delta: {})",
+            "available amount changed: (client: {}, currency: {:?}, amount: {}, delta: {})",
             client,
-            account.available_amount,
+            currency,
+            balance.available,
             amount
         );
 
-        self.used_txs.insert(tx);
+        self.used_txs.insert(tx.clone());
 
         Ok(())
     }
 
     #[doc(hidden)]
-    fn process_withdrawal(&mut self, client: Client, tx: Tx, amount: Amount) -> AnalysisResult<()> {
+    fn process_withdrawal(
+        &mut self,
+        client: Client<C>,
+        tx: Tx<T>,
+        amount: Amount,
+        currency: Option<Currency>,
+    ) -> AnalysisResult<(), C, T> {
         tracing::trace!(
-            "attempting withdrawal: (client: {}, tx: {}, amount: {})",
+            "attempting withdrawal: (client: {}, tx: {}, amount: {}, currency: {:?})",
             client,
             tx,
-            amount
+            amount,
+            currency
         );
 
-        if self.locked_accounts.contains(&client) {
-            return Err(AnalysisError::AccountLocked(client));
-        }
+        self.check_account_locked(&client, EventType::Withdrawal)?;
 
-        if self.used_txs.contains(&tx) {
-            return Err(AnalysisError::DuplicateOperation(tx));
-        }
+        self.check_tx_unique(&tx)?;
+        self.check_amount(&client, &tx, amount)?;
 
-        if amount.is_negative() {
-            return Err(AnalysisError::NegativeAmountOperation(client, tx, amount));
+        let account = self.accounts.get(&client);
+        if account.is_none() && self.config.require_account_for_withdrawal {
+            return Err(AnalysisError::AccountNotFound(client));
         }
 
-        let mut account = match self.accounts.entry(client) {
-            Entry::Occupied(entry) => entry,
-            Entry::Vacant(_) => return Err(AnalysisError::AccountNotFound(client)),
-        };
+        // New account (or a balance in a currency new to the client) has no funds.
+        // The update is checked on a copy so that nothing is created for a rejected withdrawal
+        let mut balance = account
+            .and_then(|account| account.balances.get(&currency))
+            .copied()
+            .unwrap_or_default();
 
-        if amount > account.get().available_amount {
+        if amount > balance.available {
             return Err(AnalysisError::InsufficientFunds(client, tx, amount));
         }
 
-        match account.get_mut().operations.entry(tx) {
-            Entry::Vacant(entry) => {
-                let operation = Operation {
-                    kind: operation::Kind::Withdrawal,
-                    amount,
-                };
-
-                tracing::trace!(
-                    "withdrawal operation recorded: (client: {}, tx: {}, amount: {})",
-                    client,
-                    tx,
-                    amount
-                );
-
-                entry.insert(operation);
-            }
-            // This branch shouldn't be reached
-            Entry::Occupied(_) => return Err(AnalysisError::DuplicateOperation(tx)),
+        // Transaction IDs might be unique only within client's operations
+        if account.is_some_and(|account| account.operations.contains_key(&tx)) {
+            return Err(AnalysisError::DuplicateOperation(tx));
         }
 
-        account.get_mut().available_amount -= amount;
+        balance
+            .update(balance.available.checked_sub(amount), Some(balance.held))
+            .ok_or_else(|| AnalysisError::AmountOverflow(client.clone(), tx.clone()))?;
+
+        let account = self.accounts.entry(client.clone()).or_default();
+        account.balances.insert(currency, balance);
+
+        let operation = Operation {
+            kind: operation::Kind::Withdrawal,
+            amount,
+            currency,
+            state: operation::State::Normal,
+        };
+
+        account.operations.insert(tx.clone(), operation);
 
         tracing::trace!(
-            "available amount changed: (client: {}, amount: {}, delta: {})",
+            "withdrawal operation recorded: (client: {}, tx: {}, amount: {})",
             client,
-            account.get().available_amount,
-            -amount
+            tx,
+            amount
         );
 
-        self.used_txs.insert(tx);
+        tracing::trace!(
+            "available amount changed: (client: {}, currency: {:?}, amount: {}, delta: -{})",
+            client,
+            currency,
+            balance.available,
+            amount
+        );
+
+        self.used_txs.insert(tx.clone());
 
         Ok(())
     }
 
     #[doc(hidden)]
-    fn process_dispute_init(&mut self, client: Client, tx: Tx) -> AnalysisResult<()> {
+    fn process_dispute_init(
+        &mut self,
+        client: Client<C>,
+        tx: Tx<T>,
+        currency: Option<Currency>,
+    ) -> AnalysisResult<(), C, T> {
         tracing::trace!("attempting dispute init: (client: {}, tx: {})", client, tx);
 
-        if self.locked_accounts.contains(&client) {
-            return Err(AnalysisError::AccountLocked(client));
-        }
+        self.check_account_locked(&client, EventType::Dispute)?;
 
-        if self.disputes.contains_key(&tx) {
+        if self.disputes.contains(&(client.clone(), tx.clone())) {
             return Err(AnalysisError::DisputeAlreadyInProgress(tx));
         }
 
-        let mut account = match self.accounts.entry(client) {
+        let mut account = match self.accounts.entry(client.clone()) {
             Entry::Occupied(entry) => entry,
             Entry::Vacant(_) => return Err(AnalysisError::AccountNotFound(client)),
         };
 
-        let amount = match account.get().operations.get(&tx) {
-            Some(operation) => match operation.kind {
-                operation::Kind::Deposit => operation.amount,
-                operation::Kind::Withdrawal => {
-                    return Err(AnalysisError::WithdrawalDisputeAttempt(client, tx))
-                }
-            },
+        let operation = match account.get().operations.get(&tx) {
+            Some(operation) => operation.clone(),
             None => return Err(AnalysisError::OperationNotFound(client, tx)),
         };
 
-        self.disputes.insert(tx, client);
+        if operation.currency != currency {
+            return Err(AnalysisError::CurrencyMismatch(client, tx));
+        }
 
-        tracing::trace!("dispute inited: (client: {}, tx: {})", client, tx);
+        match operation.state {
+            operation::State::Normal => {}
+            operation::State::Resolved if self.config.allow_redisputes => {}
+            operation::State::Resolved => {
+                return Err(AnalysisError::RedisputeNotAllowed(client, tx))
+            }
+            operation::State::ChargedBack => {
+                return Err(AnalysisError::OperationChargedBack(client, tx))
+            }
+            // This branch shouldn't be reached
+            operation::State::Disputed => return Err(AnalysisError::DisputeAlreadyInProgress(tx)),
+        }
 
-        account.get_mut().available_amount -= amount;
+        let amount = operation.amount;
+        let account = account.get_mut();
+        let balance = account.balances.entry(currency).or_default();
+        let (available_amount, held_amount) = (balance.available, balance.held);
+
+        let (available_amount, held_amount) =
+            match (operation.kind, self.config.withdrawal_dispute_policy) {
+                (operation::Kind::Deposit, _) => (
+                    available_amount.checked_sub(amount),
+                    held_amount.checked_add(amount),
+                ),
+                (operation::Kind::Withdrawal, WithdrawalDisputePolicy::Reject) => {
+                    return Err(AnalysisError::WithdrawalDisputeAttempt(client, tx))
+                }
+                (operation::Kind::Withdrawal, WithdrawalDisputePolicy::Hold) => {
+                    (Some(available_amount), held_amount.checked_add(amount))
+                }
+                (operation::Kind::Withdrawal, WithdrawalDisputePolicy::ProvisionalCredit) => {
+                    (available_amount.checked_add(amount), Some(held_amount))
+                }
+            };
 
-        tracing::trace!(
-            "available amount changed: (client: {}, amount: {}, delta: {})",
-            client,
-            account.get().available_amount,
-            -amount
+        balance
+            .update(available_amount, held_amount)
+            .ok_or_else(|| AnalysisError::AmountOverflow(client.clone(), tx.clone()))?;
+
+        account.operations.insert(
+            tx.clone(),
+            Operation {
+                state: operation::State::Disputed,
+                ..operation
+            },
         );
+        self.disputes.insert((client.clone(), tx.clone()));
 
-        account.get_mut().held_amount += amount;
+        tracing::trace!("dispute inited: (client: {}, tx: {})", client, tx);
 
         tracing::trace!(
-            "held amount changed: (client: {}, amount: {}, delta: {})",
+            "balance changed: (client: {}, currency: {:?}, available: {}, held: {})",
             client,
-            account.get().held_amount,
-            amount
+            currency,
+            balance.available,
+            balance.held
         );
 
         Ok(())
     }
 
     #[doc(hidden)]
-    fn process_dispute_resolve(&mut self, client: Client, tx: Tx) -> AnalysisResult<()> {
+    fn process_dispute_resolve(
+        &mut self,
+        client: Client<C>,
+        tx: Tx<T>,
+        currency: Option<Currency>,
+    ) -> AnalysisResult<(), C, T> {
         tracing::trace!(
             "attempting dispute resolve: (client: {}, tx: {})",
             client,
             tx
         );
 
-        if self.locked_accounts.contains(&client) {
-            return Err(AnalysisError::AccountLocked(client));
-        }
+        self.check_account_locked(&client, EventType::Resolve)?;
 
-        match self.disputes.get(&tx) {
-            Some(dispute_client) => {
-                if *dispute_client != client {
-                    return Err(AnalysisError::DisputeNotFound(client, tx));
-                }
-            }
-            None => return Err(AnalysisError::DisputeNotFound(client, tx)),
+        if !self.disputes.contains(&(client.clone(), tx.clone())) {
+            return Err(self.dispute_not_found_error(client, tx));
         }
 
-        let mut account = match self.accounts.entry(client) {
+        let mut account = match self.accounts.entry(client.clone()) {
             Entry::Occupied(entry) => entry,
             Entry::Vacant(_) => return Err(AnalysisError::AccountNotFound(client)),
         };
 
-        let amount = match account.get().operations.get(&tx) {
-            Some(operation) => operation.amount,
+        let operation = match account.get().operations.get(&tx) {
+            Some(operation) => operation.clone(),
             // This branch shoulbn't be reached
             None => return Err(AnalysisError::OperationNotFound(client, tx)),
         };
 
-        self.disputes.remove(&tx);
+        if operation.currency != currency {
+            return Err(AnalysisError::CurrencyMismatch(client, tx));
+        }
 
-        tracing::trace!("dispute resolved: (client: {}, tx: {})", client, tx);
+        let amount = operation.amount;
+        let account = account.get_mut();
+        let balance = account.balances.entry(currency).or_default();
+        let (available_amount, held_amount) = (balance.available, balance.held);
+
+        let (available_amount, held_amount) =
+            match (operation.kind, self.config.withdrawal_dispute_policy) {
+                (operation::Kind::Deposit, _) => (
+                    available_amount.checked_add(amount),
+                    held_amount.checked_sub(amount),
+                ),
+                // This branch shouldn't be reached
+                (operation::Kind::Withdrawal, WithdrawalDisputePolicy::Reject) => {
+                    return Err(AnalysisError::WithdrawalDisputeAttempt(client, tx))
+                }
+                (operation::Kind::Withdrawal, WithdrawalDisputePolicy::Hold) => {
+                    (Some(available_amount), held_amount.checked_sub(amount))
+                }
+                (operation::Kind::Withdrawal, WithdrawalDisputePolicy::ProvisionalCredit) => {
+                    (available_amount.checked_sub(amount), Some(held_amount))
+                }
+            };
 
-        account.get_mut().available_amount += amount;
+        balance
+            .update(available_amount, held_amount)
+            .ok_or_else(|| AnalysisError::AmountOverflow(client.clone(), tx.clone()))?;
 
-        tracing::trace!(
-            "available amount changed: (client: {}, amount: {}, delta: {})",
-            client,
-            account.get().available_amount,
-            amount
+        account.operations.insert(
+            tx.clone(),
+            Operation {
+                state: operation::State::Resolved,
+                ..operation
+            },
         );
+        self.disputes.remove(&(client.clone(), tx.clone()));
 
-        account.get_mut().held_amount -= amount;
+        tracing::trace!("dispute resolved: (client: {}, tx: {})", client, tx);
 
         tracing::trace!(
-            "held amount changed: (client: {}, amount: {}, delta: {})",
+            "balance changed: (client: {}, currency: {:?}, available: {}, held: {})",
             client,
-            account.get().held_amount,
-            -amount
+            currency,
+            balance.available,
+            balance.held
         );
 
         Ok(())
     }
 
     #[doc(hidden)]
-    fn process_dispute_chargeback(&mut self, client: Client, tx: Tx) -> AnalysisResult<()> {
+    fn process_dispute_chargeback(
+        &mut self,
+        client: Client<C>,
+        tx: Tx<T>,
+        currency: Option<Currency>,
+    ) -> AnalysisResult<(), C, T> {
         tracing::trace!(
             "attempting dispute chargeback: (client: {}, tx: {})",
             client,
             tx
         );
 
-        if self.locked_accounts.contains(&client) {
-            return Err(AnalysisError::AccountLocked(client));
-        }
+        self.check_account_locked(&client, EventType::Chargeback)?;
 
-        match self.disputes.get(&tx) {
-            Some(dispute_client) => {
-                if *dispute_client != client {
-                    return Err(AnalysisError::DisputeNotFound(client, tx));
-                }
-            }
-            None => return Err(AnalysisError::DisputeNotFound(client, tx)),
+        if !self.disputes.contains(&(client.clone(), tx.clone())) {
+            return Err(self.dispute_not_found_error(client, tx));
         }
 
-        let mut account = match self.accounts.entry(client) {
+        let mut account = match self.accounts.entry(client.clone()) {
             Entry::Occupied(entry) => entry,
             Entry::Vacant(_) => return Err(AnalysisError::AccountNotFound(client)),
         };
 
-        let amount = match account.get().operations.get(&tx) {
-            Some(operation) => operation.amount,
+        let operation = match account.get().operations.get(&tx) {
+            Some(operation) => operation.clone(),
             None => return Err(AnalysisError::OperationNotFound(client, tx)),
         };
 
-        self.disputes.remove(&tx);
+        if operation.currency != currency {
+            return Err(AnalysisError::CurrencyMismatch(client, tx));
+        }
 
-        tracing::trace!("dispute charged back: (client: {}, tx: {})", client, tx);
+        let amount = operation.amount;
+        let account = account.get_mut();
+        let balance = account.balances.entry(currency).or_default();
+        let (available_amount, held_amount) = (balance.available, balance.held);
 
-        account.get_mut().held_amount -= amount;
+        let (available_amount, held_amount) =
+            match (operation.kind, self.config.withdrawal_dispute_policy) {
+                (operation::Kind::Deposit, _) => {
+                    (Some(available_amount), held_amount.checked_sub(amount))
+                }
+                // This branch shouldn't be reached
+                (operation::Kind::Withdrawal, WithdrawalDisputePolicy::Reject) => {
+                    return Err(AnalysisError::WithdrawalDisputeAttempt(client, tx))
+                }
+                (operation::Kind::Withdrawal, WithdrawalDisputePolicy::Hold) => (
+                    available_amount.checked_add(amount),
+                    held_amount.checked_sub(amount),
+                ),
+                // Funds have already been credited when the dispute was initiated
+                (operation::Kind::Withdrawal, WithdrawalDisputePolicy::ProvisionalCredit) => {
+                    (Some(available_amount), Some(held_amount))
+                }
+            };
+
+        balance
+            .update(available_amount, held_amount)
+            .ok_or_else(|| AnalysisError::AmountOverflow(client.clone(), tx.clone()))?;
+
+        account.operations.insert(
+            tx.clone(),
+            Operation {
+                state: operation::State::ChargedBack,
+                ..operation
+            },
+        );
+        self.disputes.remove(&(client.clone(), tx.clone()));
+
+        tracing::trace!("dispute charged back: (client: {}, tx: {})", client, tx);
 
         tracing::trace!(
-            "held amount changed: (client: {}, amount: {}, delta: {})",
+            "balance changed: (client: {}, currency: {:?}, available: {}, held: {})",
             client,
-            account.get().held_amount,
-            -amount
+            currency,
+            balance.available,
+            balance.held
         );
 
-        self.locked_accounts.insert(client);
+        // Chargeback of a withdrawal is in client's favour. Hence account is never locked
+        if operation.kind == operation::Kind::Deposit && self.config.lock_on_chargeback {
+            self.locked_accounts.insert(client.clone());
+
+            tracing::trace!("account locked: (client: {})", client,);
+        }
 
-        tracing::trace!("account locked: (client: {})", client,);
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    fn process_freeze(&mut self, client: Client<C>, reason: &str) -> AnalysisResult<(), C, T> {
+        tracing::trace!("attempting freeze: (client: {})", client);
+
+        if !self.accounts.contains_key(&client) {
+            return Err(AnalysisError::AccountNotFound(client));
+        }
+
+        if !self.locked_accounts.insert(client.clone()) {
+            return Err(AnalysisError::AccountAlreadyLocked(client));
+        }
+
+        tracing::info!("account locked: (client: {}, reason: {:?})", client, reason);
+
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    fn process_unlock(&mut self, client: Client<C>, reason: &str) -> AnalysisResult<(), C, T> {
+        tracing::trace!("attempting unlock: (client: {})", client);
+
+        if !self.locked_accounts.remove(&client) {
+            return Err(AnalysisError::AccountNotLocked(client));
+        }
+
+        tracing::info!(
+            "account unlocked: (client: {}, reason: {:?})",
+            client,
+            reason
+        );
+
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    // Picks the most precise error for resolve or chargeback without a dispute in progress
+    fn dispute_not_found_error(&self, client: Client<C>, tx: Tx<T>) -> AnalysisError<C, T> {
+        let state = self
+            .accounts
+            .get(&client)
+            .and_then(|account| account.operations.get(&tx))
+            .map(|operation| operation.state);
+
+        match state {
+            Some(operation::State::Resolved) => AnalysisError::DisputeAlreadyResolved(client, tx),
+            Some(operation::State::ChargedBack) => AnalysisError::OperationChargedBack(client, tx),
+            _ => AnalysisError::DisputeNotFound(client, tx),
+        }
+    }
+
+    #[doc(hidden)]
+    fn check_account_locked(&self, client: &Client<C>, ty: EventType) -> AnalysisResult<(), C, T> {
+        if self.locked_accounts.contains(client) && !self.config.locked_account_policy.accepts(ty) {
+            return Err(AnalysisError::AccountLocked(client.clone()));
+        }
+
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    fn check_tx_unique(&self, tx: &Tx<T>) -> AnalysisResult<(), C, T> {
+        match self.config.tx_uniqueness {
+            TxUniqueness::Global if self.used_txs.contains(tx) => {
+                Err(AnalysisError::DuplicateOperation(tx.clone()))
+            }
+            // Per client uniqueness is checked against client's operations
+            _ => Ok(()),
+        }
+    }
+
+    #[doc(hidden)]
+    fn check_amount(
+        &self,
+        client: &Client<C>,
+        tx: &Tx<T>,
+        amount: Amount,
+    ) -> AnalysisResult<(), C, T> {
+        if self.config.reject_negative_amounts && amount.is_negative() {
+            return Err(AnalysisError::NegativeAmountOperation(
+                client.clone(),
+                tx.clone(),
+                amount,
+            ));
+        }
+
+        if !self.config.allow_zero_amounts && amount == Amount::ZERO {
+            return Err(AnalysisError::ZeroAmountOperation(
+                client.clone(),
+                tx.clone(),
+            ));
+        }
 
         Ok(())
     }
@@ -0,0 +1,71 @@
+use crate::event::EventType;
+
+/// Defines how disputes on [withdrawals][crate::Event::Withdrawal] are handled.
+///
+/// Unlike a disputed deposit, a disputed withdrawal is money which has already
+/// left the account. Successful [chargeback][crate::Event::Chargeback] of a
+/// withdrawal credits the funds back and **does not** lock the account.
+///
+/// | Policy                | Dispute          | Resolve          | Chargeback                  |
+/// |-----------------------|------------------|------------------|-----------------------------|
+/// | [Reject][Self::Reject]| rejected         | -                | -                           |
+/// | [Hold][Self::Hold]    | held += amount   | held -= amount   | held -= amount, available += amount |
+/// | [ProvisionalCredit][Self::ProvisionalCredit] | available += amount | available -= amount | - |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WithdrawalDisputePolicy {
+    /// Withdrawal disputes are rejected with
+    /// [WithdrawalDisputeAttempt][crate::AnalysisError::WithdrawalDisputeAttempt]
+    #[default]
+    Reject,
+    /// Disputed amount is held until the dispute is settled
+    /// and becomes available only after a chargeback
+    Hold,
+    /// Disputed amount becomes available right away
+    /// and is taken back if the dispute is resolved
+    ProvisionalCredit,
+}
+
+/// Defines which [events][crate::Event] are accepted for locked accounts.
+/// Rejected events fail with [AccountLocked][crate::AnalysisError::AccountLocked]. \
+/// [Deposits][crate::Event::Deposit] and [withdrawals][crate::Event::Withdrawal]
+/// are rejected by every policy except [AcceptAll][Self::AcceptAll].
+/// Administrative events are never rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LockedAccountPolicy {
+    /// Every event is rejected
+    #[default]
+    RejectAll,
+    /// Disputes which are already in progress can be
+    /// [resolved][crate::Event::Resolve] or [charged back][crate::Event::Chargeback].
+    /// This way funds do not get stuck in held amount
+    AllowDisputeSettlement,
+    /// Same as [AllowDisputeSettlement][Self::AllowDisputeSettlement] but new
+    /// [disputes][crate::Event::Dispute] on already recorded operations are accepted too
+    AllowDisputes,
+    /// Locked accounts are processed as usual.
+    /// Lock only shows up in [summary][crate::AccountSummary]
+    AcceptAll,
+}
+
+impl LockedAccountPolicy {
+    /// Whether an event of specified [type][EventType] is accepted for a locked account
+    pub fn accepts(self, ty: EventType) -> bool {
+        match ty {
+            EventType::Freeze | EventType::Unlock => true,
+            EventType::Resolve | EventType::Chargeback => self != Self::RejectAll,
+            EventType::Dispute => matches!(self, Self::AllowDisputes | Self::AcceptAll),
+            EventType::Deposit | EventType::Withdrawal => self == Self::AcceptAll,
+        }
+    }
+}
+
+/// Defines scope in which [transaction IDs][crate::event::wrappers::Tx] must be unique
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TxUniqueness {
+    /// Transaction IDs are unique across all clients
+    #[default]
+    Global,
+    /// Transaction IDs are unique within a single client's operations.
+    /// Different clients may reuse the same transaction ID
+    PerClient,
+}
@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+use super::{
+    account::{AccountBalance, Operation},
+    Analysis,
+};
+use crate::event::{
+    wrappers::{Client, Currency, Id, Tx},
+    Event,
+};
+
+/// Part of [Analysis] state which a single [event][Event] may change:
+/// everything related to its client and its transaction ID.
+/// Taken before the event is processed so that the event can be undone
+#[derive(Debug)]
+pub(crate) struct Savepoint<C, T> {
+    client: Client<C>,
+    /// Client's balances. `None` if the client has no account
+    balances: Option<BTreeMap<Option<Currency>, AccountBalance>>,
+    locked: bool,
+    /// Transaction of the event (if it has one) along with its state
+    tx: Option<TxSavepoint<T>>,
+}
+
+#[derive(Debug)]
+struct TxSavepoint<T> {
+    tx: Tx<T>,
+    /// Client's operation with the transaction ID
+    operation: Option<Operation>,
+    disputed: bool,
+    used: bool,
+}
+
+impl<C: Id, T: Id> Analysis<C, T> {
+    /// Saves state which processing of `event` may change
+    pub(crate) fn savepoint(&self, event: &Event<C, T>) -> Savepoint<C, T> {
+        let client = event.client();
+        let account = self.accounts.get(&client);
+
+        let tx = event.tx().map(|tx| TxSavepoint {
+            operation: account.and_then(|account| account.operations.get(&tx).cloned()),
+            disputed: self.disputes.contains(&(client.clone(), tx.clone())),
+            used: self.used_txs.contains(&tx),
+            tx,
+        });
+
+        Savepoint {
+            balances: account.map(|account| account.balances.clone()),
+            locked: self.locked_accounts.contains(&client),
+            client,
+            tx,
+        }
+    }
+
+    /// Restores state saved before an event has been processed.
+    /// Must be called right after that event, before any other one is processed
+    pub(crate) fn rollback(&mut self, savepoint: Savepoint<C, T>) {
+        let Savepoint {
+            client,
+            balances,
+            locked,
+            tx,
+        } = savepoint;
+
+        match balances {
+            Some(balances) => {
+                // Account existed, so the event could not have removed it
+                let account = self.accounts.get_mut(&client).expect("account removed");
+                account.balances = balances;
+
+                if let Some(TxSavepoint { tx, operation, .. }) = &tx {
+                    match operation {
+                        Some(operation) => account.operations.insert(tx.clone(), operation.clone()),
+                        None => account.operations.remove(tx),
+                    };
+                }
+            }
+            None => {
+                self.accounts.remove(&client);
+            }
+        }
+
+        if locked {
+            self.locked_accounts.insert(client.clone());
+        } else {
+            self.locked_accounts.remove(&client);
+        }
+
+        if let Some(TxSavepoint {
+            tx, disputed, used, ..
+        }) = tx
+        {
+            if !used {
+                self.used_txs.remove(&tx);
+            }
+
+            if disputed {
+                self.disputes.insert((client, tx));
+            } else {
+                self.disputes.remove(&(client, tx));
+            }
+        }
+    }
+}
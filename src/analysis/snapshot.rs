@@ -0,0 +1,366 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{Read, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    account::{operation, Account, AccountBalance, Operation},
+    Analysis, AnalysisConfig,
+};
+use crate::event::wrappers::{Amount, Client, Currency, Id, Tx};
+
+/// Error which can occur while taking or restoring an [Analysis] snapshot
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Snapshot could not be written or read
+    #[error("snapshot io error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Snapshot is not a valid document of its declared format version
+    #[error("malformed snapshot: {0}")]
+    Malformed(#[from] serde_json::Error),
+    /// Snapshot has been produced by an incompatible version of this crate
+    #[error(
+        "unsupported snapshot version: {0} (supported: {} to {})",
+        VERSION_1,
+        VERSION
+    )]
+    UnsupportedVersion(u64),
+    /// Snapshot is well formed but describes an impossible state
+    #[error("inconsistent snapshot: {0}")]
+    Inconsistent(String),
+}
+
+/// On-disk representation of [Analysis] state.
+///
+/// The snapshot is a JSON document tagged with a format `version`.
+/// Collections are sorted so that equal states produce equal snapshots.
+/// [Configuration][AnalysisConfig] is not part of the snapshot:
+/// it is supplied anew when the snapshot is [restored][Analysis::restore]
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot<C, T> {
+    version: u64,
+    accounts: Vec<AccountSnapshot<C, T>>,
+    disputes: Vec<DisputeSnapshot<C, T>>,
+    locked_accounts: Vec<Client<C>>,
+    used_txs: Vec<Tx<T>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AccountSnapshot<C, T> {
+    client: Client<C>,
+    balances: Vec<BalanceSnapshot>,
+    operations: Vec<OperationSnapshot<T>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BalanceSnapshot {
+    currency: Option<Currency>,
+    available: Amount,
+    held: Amount,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OperationSnapshot<T> {
+    tx: Tx<T>,
+    kind: operation::Kind,
+    amount: Amount,
+    currency: Option<Currency>,
+    state: operation::State,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DisputeSnapshot<C, T> {
+    client: Client<C>,
+    tx: Tx<T>,
+}
+
+/// Snapshot format version 1, with a single balance per client.
+/// Read only: it is [upgraded][SnapshotV1::upgrade] to the current format on restore
+#[derive(Debug, Deserialize)]
+struct SnapshotV1<C, T> {
+    accounts: Vec<AccountSnapshotV1<C, T>>,
+    disputes: Vec<DisputeSnapshot<C, T>>,
+    locked_accounts: Vec<Client<C>>,
+    used_txs: Vec<Tx<T>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountSnapshotV1<C, T> {
+    client: Client<C>,
+    available: Amount,
+    held: Amount,
+    operations: Vec<OperationSnapshotV1<T>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OperationSnapshotV1<T> {
+    tx: Tx<T>,
+    kind: operation::Kind,
+    amount: Amount,
+    state: operation::State,
+}
+
+/// Minimal view of a snapshot used to check its version before decoding the rest
+#[derive(Debug, Deserialize)]
+struct SnapshotVersion {
+    version: u64,
+}
+
+/// Current snapshot format version.
+/// Must be bumped on every incompatible change of the format
+///
+/// Version 2 tracks balances per currency
+const VERSION: u64 = 2;
+
+/// Oldest snapshot format version which can still be restored
+const VERSION_1: u64 = 1;
+
+impl<C, T> SnapshotV1<C, T> {
+    /// Converts to the current format.
+    /// Single balance and all operations of a client are the ones without a currency
+    fn upgrade(self) -> Snapshot<C, T> {
+        let accounts = self
+            .accounts
+            .into_iter()
+            .map(|account| AccountSnapshot {
+                client: account.client,
+                balances: vec![BalanceSnapshot {
+                    currency: None,
+                    available: account.available,
+                    held: account.held,
+                }],
+                operations: account
+                    .operations
+                    .into_iter()
+                    .map(|operation| OperationSnapshot {
+                        tx: operation.tx,
+                        kind: operation.kind,
+                        amount: operation.amount,
+                        currency: None,
+                        state: operation.state,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Snapshot {
+            version: VERSION,
+            accounts,
+            disputes: self.disputes,
+            locked_accounts: self.locked_accounts,
+            used_txs: self.used_txs,
+        }
+    }
+}
+
+impl<C: Id, T: Id> Snapshot<C, T> {
+    fn take(analysis: &Analysis<C, T>) -> Self {
+        let mut accounts = analysis
+            .accounts
+            .iter()
+            .map(|(client, account)| {
+                let mut operations = account
+                    .operations
+                    .iter()
+                    .map(|(tx, operation)| OperationSnapshot {
+                        tx: tx.clone(),
+                        kind: operation.kind,
+                        amount: operation.amount,
+                        currency: operation.currency,
+                        state: operation.state,
+                    })
+                    .collect::<Vec<_>>();
+                operations.sort_unstable_by(|a, b| a.tx.cmp(&b.tx));
+
+                // Balances are already ordered by currency
+                let balances = account
+                    .balances
+                    .iter()
+                    .map(|(currency, balance)| BalanceSnapshot {
+                        currency: *currency,
+                        available: balance.available,
+                        held: balance.held,
+                    })
+                    .collect();
+
+                AccountSnapshot {
+                    client: client.clone(),
+                    balances,
+                    operations,
+                }
+            })
+            .collect::<Vec<_>>();
+        accounts.sort_unstable_by(|a, b| a.client.cmp(&b.client));
+
+        let mut disputes = analysis
+            .disputes
+            .iter()
+            .map(|(client, tx)| DisputeSnapshot {
+                client: client.clone(),
+                tx: tx.clone(),
+            })
+            .collect::<Vec<_>>();
+        disputes.sort_unstable_by(|a, b| (&a.client, &a.tx).cmp(&(&b.client, &b.tx)));
+
+        let mut locked_accounts = analysis.locked_accounts.iter().cloned().collect::<Vec<_>>();
+        locked_accounts.sort_unstable();
+
+        let mut used_txs = analysis.used_txs.iter().cloned().collect::<Vec<_>>();
+        used_txs.sort_unstable();
+
+        Self {
+            version: VERSION,
+            accounts,
+            disputes,
+            locked_accounts,
+            used_txs,
+        }
+    }
+
+    fn into_analysis(self, config: AnalysisConfig) -> Result<Analysis<C, T>, Error> {
+        let mut accounts = HashMap::with_capacity(self.accounts.len());
+
+        for account in self.accounts {
+            let mut balances = BTreeMap::new();
+
+            for balance in account.balances {
+                if balance.available.checked_add(balance.held).is_none() {
+                    return Err(Error::Inconsistent(format!(
+                        "total amount overflow (client: {}, currency: {:?})",
+                        account.client, balance.currency
+                    )));
+                }
+
+                let duplicate = balances
+                    .insert(
+                        balance.currency,
+                        AccountBalance {
+                            available: balance.available,
+                            held: balance.held,
+                        },
+                    )
+                    .is_some();
+
+                if duplicate {
+                    return Err(Error::Inconsistent(format!(
+                        "duplicate balance (client: {}, currency: {:?})",
+                        account.client, balance.currency
+                    )));
+                }
+            }
+
+            let mut operations = HashMap::with_capacity(account.operations.len());
+
+            for operation in account.operations {
+                let duplicate = operations
+                    .insert(
+                        operation.tx.clone(),
+                        Operation {
+                            kind: operation.kind,
+                            amount: operation.amount,
+                            currency: operation.currency,
+                            state: operation.state,
+                        },
+                    )
+                    .is_some();
+
+                if duplicate {
+                    return Err(Error::Inconsistent(format!(
+                        "duplicate operation (client: {}, tx: {})",
+                        account.client, operation.tx
+                    )));
+                }
+            }
+
+            let account_state = Account {
+                balances,
+                operations,
+            };
+
+            if accounts
+                .insert(account.client.clone(), account_state)
+                .is_some()
+            {
+                return Err(Error::Inconsistent(format!(
+                    "duplicate account (client: {})",
+                    account.client
+                )));
+            }
+        }
+
+        let mut disputes = HashSet::with_capacity(self.disputes.len());
+
+        for DisputeSnapshot { client, tx } in self.disputes {
+            let state = accounts
+                .get(&client)
+                .and_then(|account: &Account<T>| account.operations.get(&tx))
+                .map(|operation| operation.state);
+
+            if state != Some(operation::State::Disputed) {
+                return Err(Error::Inconsistent(format!(
+                    "dispute on operation which is not disputed (client: {}, tx: {})",
+                    client, tx
+                )));
+            }
+
+            disputes.insert((client, tx));
+        }
+
+        Ok(Analysis {
+            accounts,
+            disputes,
+            locked_accounts: self.locked_accounts.into_iter().collect(),
+            used_txs: self.used_txs.into_iter().collect(),
+            config,
+        })
+    }
+}
+
+impl<C: Id, T: Id> Analysis<C, T> {
+    /// Writes complete analysis state to `writer` in a versioned snapshot format.
+    /// The snapshot can be turned back into [Analysis] with [Analysis::restore]
+    /// to continue processing events incrementally.
+    ///
+    /// **[Configuration][AnalysisConfig] is not saved to the snapshot**
+    pub fn snapshot<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        serde_json::to_writer(&mut writer, &Snapshot::take(self))?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Restores analysis state previously written with [Analysis::snapshot].
+    /// Restored analysis follows specified [configuration][AnalysisConfig].
+    ///
+    /// Snapshots of format version 1 (single balance per client) are accepted as well:
+    /// their balances and operations are restored as the ones without a currency
+    ///
+    /// # Example
+    /// ```
+    /// use scoring::{Analysis, AnalysisConfig};
+    ///
+    /// let mut snapshot = Vec::new();
+    /// Analysis::begin().snapshot(&mut snapshot).unwrap();
+    ///
+    /// let analysis = Analysis::restore(snapshot.as_slice(), AnalysisConfig::default()).unwrap();
+    /// assert_eq!(analysis, Analysis::begin());
+    /// ```
+    pub fn restore<R: Read>(mut reader: R, config: AnalysisConfig) -> Result<Self, Error> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+
+        // Version is checked first so that snapshots of other versions
+        // are reported as such rather than as malformed ones
+        let SnapshotVersion { version } = serde_json::from_slice(&buf)?;
+        let snapshot = match version {
+            VERSION => serde_json::from_slice::<Snapshot<C, T>>(&buf)?,
+            VERSION_1 => serde_json::from_slice::<SnapshotV1<C, T>>(&buf)?.upgrade(),
+            _ => return Err(Error::UnsupportedVersion(version)),
+        };
+
+        snapshot.into_analysis(config)
+    }
+}
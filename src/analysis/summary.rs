@@ -1,51 +1,90 @@
-use std::{collections::HashSet, iter::Iterator};
+use std::{fmt, iter::Iterator, str::FromStr, vec};
 
-use super::{
-    account::{Account, AccountSummary},
-    Analysis,
-};
-use crate::event::wrappers::Client;
+use super::{account::AccountSummary, Analysis};
+use crate::event::wrappers::Id;
+
+/// Order in which [AnalysisSummary] yields [account summaries][AccountSummary]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SummaryOrder {
+    /// Ascending by [client ID][crate::event::wrappers::Client].
+    /// Balances of a client are ordered by [currency][crate::event::wrappers::Currency]
+    #[default]
+    Client,
+    /// Ascending by total amount (available + held).
+    /// Balances with equal total amounts are ordered by [client ID][crate::event::wrappers::Client]
+    /// and [currency][crate::event::wrappers::Currency].
+    /// Amounts in different currencies are compared as they are
+    Total,
+}
+
+impl FromStr for SummaryOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "client" => Ok(Self::Client),
+            "total" => Ok(Self::Total),
+            _ => Err(format!("unknown summary order: {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for SummaryOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Client => write!(f, "client"),
+            Self::Total => write!(f, "total"),
+        }
+    }
+}
 
 #[derive(Debug)]
 /// Represents [Analysis] symmary and can be conveniently obtained
 /// from an [Analysis] instance by calling [Analysis::summary]
+/// or from a borrowed one by calling [Analysis::current_summary]
 ///
-/// Implements [Iterator] of [AccountSummary] for natural sequential processing
-pub struct AnalysisSummary {
+/// Implements [Iterator] of [AccountSummary] for natural sequential processing.
+/// Every client's balance (one per currency) is yielded as a separate summary.
+/// Accounts are yielded in [SummaryOrder::Client] order unless
+/// other [order][SummaryOrder] is requested via [Analysis::summary_ordered]
+pub struct AnalysisSummary<C = u16> {
     #[doc(hidden)]
-    accounts: Vec<(Client, Account)>,
+    accounts: vec::IntoIter<AccountSummary<C>>,
+}
+
+impl<C: Id> AnalysisSummary<C> {
     #[doc(hidden)]
-    locked: HashSet<Client>,
+    pub(super) fn new<T: Id>(analysis: &Analysis<C, T>, order: SummaryOrder) -> Self {
+        let mut accounts = analysis
+            .iter_accounts()
+            .flat_map(|account| account.summaries())
+            .collect::<Vec<_>>();
+
+        match order {
+            SummaryOrder::Client => accounts
+                .sort_unstable_by(|a, b| (&a.client, a.currency).cmp(&(&b.client, b.currency))),
+            SummaryOrder::Total => accounts.sort_unstable_by(|a, b| {
+                (a.total(), &a.client, a.currency).cmp(&(b.total(), &b.client, b.currency))
+            }),
+        }
+
+        Self {
+            accounts: accounts.into_iter(),
+        }
+    }
 }
 
-impl Iterator for AnalysisSummary {
-    type Item = AccountSummary;
+impl<C> Iterator for AnalysisSummary<C> {
+    type Item = AccountSummary<C>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.accounts.pop().map(|(client, account)| {
-            let held = account.held_amount;
-            let available = account.available_amount;
-
-            AccountSummary {
-                client,
-                available: available.to_string(),
-                held: held.to_string(),
-                total: (available + held).to_string(),
-                locked: self.locked.contains(&client),
-            }
-        })
+        self.accounts.next()
     }
 }
 
-impl From<Analysis> for AnalysisSummary {
-    fn from(analysis: Analysis) -> Self {
-        // transform HashMap to Vec for ease of iteration
-        let accounts = analysis.accounts.into_iter().collect();
-
-        Self {
-            accounts,
-            locked: analysis.locked_accounts,
-        }
+impl<C: Id, T: Id> From<Analysis<C, T>> for AnalysisSummary<C> {
+    fn from(analysis: Analysis<C, T>) -> Self {
+        Self::new(&analysis, SummaryOrder::default())
     }
 }
 
@@ -55,7 +94,7 @@ mod tests {
 
     use super::*;
     use crate::{
-        event::wrappers::{Amount, Tx},
+        event::wrappers::{Amount, Client, Tx},
         AccountSummary, Event,
     };
 
@@ -69,20 +108,23 @@ mod tests {
             .process_event(&Event::Deposit {
                 client: Client(1),
                 tx: Tx(1),
-                amount: Amount(1.0),
+                amount: Amount::from(1),
+                currency: None,
             })
             .unwrap();
         analysis
             .process_event(&Event::Deposit {
                 client: Client(1),
                 tx: Tx(2),
-                amount: Amount(1.0),
+                amount: Amount::from(1),
+                currency: None,
             })
             .unwrap();
         analysis
             .process_event(&Event::Dispute {
                 client: Client(1),
                 tx: Tx(1),
+                currency: None,
             })
             .unwrap();
 
@@ -90,26 +132,30 @@ mod tests {
             .process_event(&Event::Deposit {
                 client: Client(2),
                 tx: Tx(3),
-                amount: Amount(10.0),
+                amount: Amount::from(10),
+                currency: None,
             })
             .unwrap();
         analysis
             .process_event(&Event::Withdrawal {
                 client: Client(2),
                 tx: Tx(4),
-                amount: Amount(1.0),
+                amount: Amount::from(1),
+                currency: None,
             })
             .unwrap();
         analysis
             .process_event(&Event::Dispute {
                 client: Client(2),
                 tx: Tx(3),
+                currency: None,
             })
             .unwrap();
         analysis
             .process_event(&Event::Resolve {
                 client: Client(2),
                 tx: Tx(3),
+                currency: None,
             })
             .unwrap();
 
@@ -117,26 +163,30 @@ mod tests {
             .process_event(&Event::Deposit {
                 client: Client(3),
                 tx: Tx(5),
-                amount: Amount(10.0),
+                amount: Amount::from(10),
+                currency: None,
             })
             .unwrap();
         analysis
             .process_event(&Event::Deposit {
                 client: Client(3),
                 tx: Tx(6),
-                amount: Amount(1.0),
+                amount: Amount::from(1),
+                currency: None,
             })
             .unwrap();
         analysis
             .process_event(&Event::Dispute {
                 client: Client(3),
                 tx: Tx(5),
+                currency: None,
             })
             .unwrap();
         analysis
             .process_event(&Event::Chargeback {
                 client: Client(3),
                 tx: Tx(5),
+                currency: None,
             })
             .unwrap();
 
@@ -147,23 +197,23 @@ mod tests {
             [
                 AccountSummary {
                     client: Client(1),
-                    available: String::from("1.0000"),
-                    held: String::from("1.0000"),
-                    total: String::from("2.0000"),
+                    currency: None,
+                    available: Amount::from(1),
+                    held: Amount::from(1),
                     locked: false
                 },
                 AccountSummary {
                     client: Client(2),
-                    available: String::from("9.0000"),
-                    held: String::from("0.0000"),
-                    total: String::from("9.0000"),
+                    currency: None,
+                    available: Amount::from(9),
+                    held: Amount::from(0),
                     locked: false
                 },
                 AccountSummary {
                     client: Client(3),
-                    available: String::from("1.0000"),
-                    held: String::from("0.0000"),
-                    total: String::from("1.0000"),
+                    currency: None,
+                    available: Amount::from(1),
+                    held: Amount::from(0),
                     locked: true
                 },
             ]
@@ -171,4 +221,129 @@ mod tests {
             .collect::<HashSet<AccountSummary>>()
         )
     }
+
+    #[test]
+    fn test_order() {
+        let mut analysis = Analysis::begin();
+
+        let deposits = [
+            (Client(3), 1),
+            (Client(1), 3),
+            (Client(2), 2),
+            (Client(4), 2),
+        ];
+
+        for (tx, (client, amount)) in deposits.into_iter().enumerate() {
+            analysis
+                .process_event(&Event::Deposit {
+                    client,
+                    tx: Tx(tx as u32),
+                    amount: Amount::from(amount),
+                    currency: None,
+                })
+                .unwrap();
+        }
+
+        let clients = |order| {
+            analysis
+                .clone()
+                .summary_ordered(order)
+                .map(|summary| summary.client)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            clients(SummaryOrder::Client),
+            vec![Client(1), Client(2), Client(3), Client(4)]
+        );
+        assert_eq!(
+            clients(SummaryOrder::Total),
+            vec![Client(3), Client(2), Client(4), Client(1)]
+        );
+        assert_eq!(
+            analysis
+                .summary()
+                .map(|summary| summary.client)
+                .collect::<Vec<_>>(),
+            vec![Client(1), Client(2), Client(3), Client(4)]
+        );
+    }
+
+    #[test]
+    fn test_account_summary() {
+        let mut analysis = Analysis::begin();
+
+        analysis
+            .process_event(&Event::Deposit {
+                client: Client(1),
+                tx: Tx(1),
+                amount: Amount::from(2),
+                currency: None,
+            })
+            .unwrap();
+        analysis
+            .process_event(&Event::Dispute {
+                client: Client(1),
+                tx: Tx(1),
+                currency: None,
+            })
+            .unwrap();
+
+        assert_eq!(
+            analysis.account_summary(Client(1), None),
+            Some(AccountSummary {
+                client: Client(1),
+                currency: None,
+                available: Amount::from(0),
+                held: Amount::from(2),
+                locked: false
+            })
+        );
+        assert_eq!(analysis.account_summary(Client(2), None), None);
+    }
+
+    #[test]
+    fn test_current_summary() {
+        let mut analysis = Analysis::begin();
+
+        analysis
+            .process_event(&Event::Deposit {
+                client: Client(2),
+                tx: Tx(1),
+                amount: Amount::from(1),
+                currency: None,
+            })
+            .unwrap();
+
+        let summary = analysis
+            .current_summary(SummaryOrder::Client)
+            .collect::<Vec<_>>();
+
+        analysis
+            .process_event(&Event::Deposit {
+                client: Client(1),
+                tx: Tx(2),
+                amount: Amount::from(2),
+                currency: None,
+            })
+            .unwrap();
+
+        assert_eq!(
+            summary,
+            vec![AccountSummary {
+                client: Client(2),
+                currency: None,
+                available: Amount::from(1),
+                held: Amount::from(0),
+                locked: false
+            }]
+        );
+        assert_eq!(
+            analysis
+                .current_summary(SummaryOrder::Total)
+                .map(|summary| summary.client)
+                .collect::<Vec<_>>(),
+            vec![Client(2), Client(1)]
+        );
+    }
 }
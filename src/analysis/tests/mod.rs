@@ -1,8 +1,18 @@
+mod test_analysis_config;
+mod test_currency;
+mod test_ids;
 mod test_process_deposit;
 mod test_process_dispute_chargeback;
 mod test_process_dispute_init;
 mod test_process_dispute_resolve;
+mod test_process_freeze;
+mod test_process_unlock;
 mod test_process_withdrawal;
+mod test_process_withdrawal_dispute_chargeback;
+mod test_process_withdrawal_dispute_init;
+mod test_process_withdrawal_dispute_resolve;
+mod test_query;
+mod test_snapshot;
 mod utils;
 
 use super::*;
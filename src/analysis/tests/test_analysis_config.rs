@@ -0,0 +1,456 @@
+use super::*;
+
+#[test]
+fn test_zero_amounts_disallowed() {
+    let mut analysis = Analysis::with_config(AnalysisConfig::default().allow_zero_amounts(false));
+
+    let client = Client(1);
+    let tx = Tx(1);
+    let amount = Amount::ZERO;
+
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::ZeroAmountOperation(client, tx)));
+
+    utils::assert_account_not_exists(&analysis, client);
+
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount: Amount::from(1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let tx = Tx(2);
+
+    let event = Event::Withdrawal {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::ZeroAmountOperation(client, tx)));
+
+    utils::assert_operations_count(&analysis, client, 1);
+}
+
+#[test]
+fn test_negative_amounts_allowed() {
+    let mut analysis =
+        Analysis::with_config(AnalysisConfig::default().reject_negative_amounts(false));
+
+    let client = Client(1);
+
+    let event = Event::Deposit {
+        client,
+        tx: Tx(1),
+        amount: Amount::from(-1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(-1), Amount::from(0));
+
+    let event = Event::Withdrawal {
+        client,
+        tx: Tx(2),
+        amount: Amount::from(-3),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(2), Amount::from(0));
+    utils::assert_operations_count(&analysis, client, 2);
+}
+
+#[test]
+fn test_no_lock_on_chargeback() {
+    let mut analysis = Analysis::with_config(AnalysisConfig::default().lock_on_chargeback(false));
+
+    let client = Client(1);
+    let tx = Tx(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx,
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Chargeback {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Deposit {
+            client,
+            tx: Tx(2),
+            amount: Amount::from(2),
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    utils::assert_account_balance(&analysis, client, Amount::from(2), Amount::from(0));
+    utils::assert_account_not_locked(&analysis, client);
+}
+
+#[test]
+fn test_locked_account_accept_all() {
+    let mut analysis = Analysis::with_config(
+        AnalysisConfig::default().locked_account_policy(LockedAccountPolicy::AcceptAll),
+    );
+
+    let client = Client(1);
+    let tx = Tx(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx,
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Chargeback {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Deposit {
+            client,
+            tx: Tx(2),
+            amount: Amount::from(2),
+            currency: None,
+        },
+        Event::Withdrawal {
+            client,
+            tx: Tx(3),
+            amount: Amount::from(1),
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
+    utils::assert_account_locked(&analysis, client);
+}
+
+#[test]
+fn test_tx_uniqueness_per_client() {
+    let mut analysis =
+        Analysis::with_config(AnalysisConfig::default().tx_uniqueness(TxUniqueness::PerClient));
+
+    let tx = Tx(1);
+    let amount = Amount::from(1);
+
+    for client in [Client(1), Client(2)] {
+        let event = Event::Deposit {
+            client,
+            tx,
+            amount,
+            currency: None,
+        };
+
+        let result = analysis.process_event(&event);
+        assert_eq!(result, Ok(()));
+    }
+
+    // Transaction IDs are still unique within client's operations
+    let event = Event::Withdrawal {
+        client: Client(1),
+        tx,
+        amount,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::DuplicateOperation(tx)));
+
+    // Disputes on the same transaction ID are independent
+    let event = Event::Dispute {
+        client: Client(1),
+        tx,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_dispute_exists(&analysis, Client(1), tx);
+    utils::assert_dispute_not_exists(&analysis, Client(2), tx);
+
+    utils::assert_account_balance(&analysis, Client(1), Amount::from(0), Amount::from(1));
+    utils::assert_account_balance(&analysis, Client(2), Amount::from(1), Amount::from(0));
+}
+
+#[test]
+fn test_withdrawal_without_account() {
+    let mut analysis =
+        Analysis::with_config(AnalysisConfig::default().require_account_for_withdrawal(false));
+
+    let client = Client(1);
+    let tx = Tx(1);
+    let amount = Amount::from(1);
+
+    let event = Event::Withdrawal {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
+
+    // There are no funds on a new account
+    let result = analysis.process_event(&event);
+    assert_eq!(
+        result,
+        Err(AnalysisError::InsufficientFunds(client, tx, amount))
+    );
+
+    utils::assert_account_not_exists(&analysis, client);
+
+    let event = Event::Withdrawal {
+        client,
+        tx,
+        amount: Amount::ZERO,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(0));
+    utils::assert_operations_count(&analysis, client, 1);
+}
+
+#[test]
+fn test_locked_account_allow_dispute_settlement() {
+    let mut analysis = Analysis::with_config(
+        AnalysisConfig::default()
+            .locked_account_policy(LockedAccountPolicy::AllowDisputeSettlement),
+    );
+
+    let client = Client(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx: Tx(1),
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Deposit {
+            client,
+            tx: Tx(2),
+            amount: Amount::from(2),
+            currency: None,
+        },
+        Event::Deposit {
+            client,
+            tx: Tx(3),
+            amount: Amount::from(4),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx: Tx(1),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx: Tx(2),
+            currency: None,
+        },
+        Event::Chargeback {
+            client,
+            tx: Tx(1),
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    utils::assert_account_locked(&analysis, client);
+    utils::assert_account_balance(&analysis, client, Amount::from(4), Amount::from(2));
+
+    // Dispute which is already in progress can be settled
+    let event = Event::Resolve {
+        client,
+        tx: Tx(2),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(6), Amount::from(0));
+    utils::assert_disputes_count(&analysis, 0);
+
+    // New disputes are not allowed
+    let event = Event::Dispute {
+        client,
+        tx: Tx(3),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::AccountLocked(client)));
+
+    // Neither are new operations
+    let event = Event::Deposit {
+        client,
+        tx: Tx(4),
+        amount: Amount::from(1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::AccountLocked(client)));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(6), Amount::from(0));
+    utils::assert_operations_count(&analysis, client, 3);
+}
+
+#[test]
+fn test_locked_account_allow_disputes() {
+    let mut analysis = Analysis::with_config(
+        AnalysisConfig::default().locked_account_policy(LockedAccountPolicy::AllowDisputes),
+    );
+
+    let client = Client(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx: Tx(1),
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Deposit {
+            client,
+            tx: Tx(2),
+            amount: Amount::from(2),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx: Tx(1),
+            currency: None,
+        },
+        Event::Chargeback {
+            client,
+            tx: Tx(1),
+            currency: None,
+        },
+        // Account is locked at this point
+        Event::Dispute {
+            client,
+            tx: Tx(2),
+            currency: None,
+        },
+        Event::Chargeback {
+            client,
+            tx: Tx(2),
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    utils::assert_account_locked(&analysis, client);
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(0));
+
+    let event = Event::Withdrawal {
+        client,
+        tx: Tx(3),
+        amount: Amount::from(0),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::AccountLocked(client)));
+
+    utils::assert_operations_count(&analysis, client, 2);
+}
+
+#[test]
+fn test_redisputes_disallowed() {
+    let mut analysis = Analysis::with_config(AnalysisConfig::default().allow_redisputes(false));
+
+    let client = Client(1);
+    let tx = Tx(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx,
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Resolve {
+            client,
+            tx,
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    let event = Event::Dispute {
+        client,
+        tx,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::RedisputeNotAllowed(client, tx)));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
+    utils::assert_disputes_count(&analysis, 0);
+}
@@ -0,0 +1,351 @@
+use super::*;
+
+use pretty_assertions::assert_eq;
+
+fn currency(code: &str) -> Option<Currency> {
+    Some(code.parse().unwrap())
+}
+
+fn deposit(client: u16, tx: u32, amount: i32, code: &str) -> Event {
+    Event::Deposit {
+        client: Client(client),
+        tx: Tx(tx),
+        amount: Amount::from(amount),
+        currency: currency(code),
+    }
+}
+
+fn balance(analysis: &Analysis, client: u16, code: &str) -> Option<AccountBalance> {
+    analysis.account(Client(client))?.balance(currency(code))
+}
+
+#[test]
+fn test_balances_per_currency() {
+    let mut analysis = Analysis::begin();
+
+    let events = [
+        deposit(1, 1, 10, "USD"),
+        deposit(1, 2, 5, "EUR"),
+        Event::Withdrawal {
+            client: Client(1),
+            tx: Tx(3),
+            amount: Amount::from(3),
+            currency: currency("usd"),
+        },
+        Event::Deposit {
+            client: Client(1),
+            tx: Tx(4),
+            amount: Amount::from(1),
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        assert_eq!(analysis.process_event(event), Ok(()));
+    }
+
+    assert_eq!(
+        balance(&analysis, 1, "USD"),
+        Some(AccountBalance {
+            available: Amount::from(7),
+            held: Amount::from(0),
+        })
+    );
+    assert_eq!(
+        balance(&analysis, 1, "EUR"),
+        Some(AccountBalance {
+            available: Amount::from(5),
+            held: Amount::from(0),
+        })
+    );
+    assert_eq!(balance(&analysis, 1, "GBP"), None);
+    utils::assert_account_balance(&analysis, Client(1), Amount::from(1), Amount::from(0));
+
+    // Funds in other currencies do not count
+    let event = Event::Withdrawal {
+        client: Client(1),
+        tx: Tx(5),
+        amount: Amount::from(6),
+        currency: currency("EUR"),
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(
+        result,
+        Err(AnalysisError::InsufficientFunds(
+            Client(1),
+            Tx(5),
+            Amount::from(6)
+        ))
+    );
+
+    let event = Event::Withdrawal {
+        client: Client(1),
+        tx: Tx(6),
+        amount: Amount::from(1),
+        currency: currency("GBP"),
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(
+        result,
+        Err(AnalysisError::InsufficientFunds(
+            Client(1),
+            Tx(6),
+            Amount::from(1)
+        ))
+    );
+    assert_eq!(balance(&analysis, 1, "GBP"), None);
+}
+
+#[test]
+fn test_dispute_currency() {
+    let mut analysis = Analysis::begin();
+
+    let events = [deposit(1, 1, 10, "USD"), deposit(1, 2, 5, "EUR")];
+
+    for event in events.iter() {
+        assert_eq!(analysis.process_event(event), Ok(()));
+    }
+
+    for dispute_currency in [None, currency("EUR")] {
+        let event = Event::Dispute {
+            client: Client(1),
+            tx: Tx(1),
+            currency: dispute_currency,
+        };
+
+        let result = analysis.process_event(&event);
+        assert_eq!(
+            result,
+            Err(AnalysisError::CurrencyMismatch(Client(1), Tx(1)))
+        );
+    }
+    utils::assert_disputes_count(&analysis, 0);
+
+    let event = Event::Dispute {
+        client: Client(1),
+        tx: Tx(1),
+        currency: currency("USD"),
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    assert_eq!(
+        balance(&analysis, 1, "USD"),
+        Some(AccountBalance {
+            available: Amount::from(0),
+            held: Amount::from(10),
+        })
+    );
+    assert_eq!(
+        balance(&analysis, 1, "EUR"),
+        Some(AccountBalance {
+            available: Amount::from(5),
+            held: Amount::from(0),
+        })
+    );
+
+    let event = Event::Resolve {
+        client: Client(1),
+        tx: Tx(1),
+        currency: currency("EUR"),
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(
+        result,
+        Err(AnalysisError::CurrencyMismatch(Client(1), Tx(1)))
+    );
+
+    let event = Event::Chargeback {
+        client: Client(1),
+        tx: Tx(1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(
+        result,
+        Err(AnalysisError::CurrencyMismatch(Client(1), Tx(1)))
+    );
+    utils::assert_dispute_exists(&analysis, Client(1), Tx(1));
+
+    let event = Event::Chargeback {
+        client: Client(1),
+        tx: Tx(1),
+        currency: currency("USD"),
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    assert_eq!(
+        balance(&analysis, 1, "USD"),
+        Some(AccountBalance {
+            available: Amount::from(0),
+            held: Amount::from(0),
+        })
+    );
+
+    // Account is locked in all currencies
+    utils::assert_account_locked(&analysis, Client(1));
+
+    let result = analysis.process_event(&deposit(1, 3, 1, "EUR"));
+    assert_eq!(result, Err(AnalysisError::AccountLocked(Client(1))));
+}
+
+#[test]
+fn test_summary() {
+    let mut analysis = Analysis::begin();
+
+    let events = [
+        deposit(2, 1, 1, "USD"),
+        deposit(1, 2, 3, "USD"),
+        deposit(1, 3, 2, "EUR"),
+        Event::Deposit {
+            client: Client(1),
+            tx: Tx(4),
+            amount: Amount::from(4),
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        assert_eq!(analysis.process_event(event), Ok(()));
+    }
+
+    let rows = |order| {
+        analysis
+            .current_summary(order)
+            .map(|summary| (summary.client, summary.currency, summary.total()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        rows(SummaryOrder::Client),
+        vec![
+            (Client(1), None, Amount::from(4)),
+            (Client(1), currency("EUR"), Amount::from(2)),
+            (Client(1), currency("USD"), Amount::from(3)),
+            (Client(2), currency("USD"), Amount::from(1)),
+        ]
+    );
+    assert_eq!(
+        rows(SummaryOrder::Total),
+        vec![
+            (Client(2), currency("USD"), Amount::from(1)),
+            (Client(1), currency("EUR"), Amount::from(2)),
+            (Client(1), currency("USD"), Amount::from(3)),
+            (Client(1), None, Amount::from(4)),
+        ]
+    );
+    assert_eq!(
+        analysis.account_summary(Client(1), currency("EUR")),
+        Some(AccountSummary {
+            client: Client(1),
+            currency: currency("EUR"),
+            available: Amount::from(2),
+            held: Amount::from(0),
+            locked: false,
+        })
+    );
+    assert_eq!(analysis.account_summary(Client(2), None), None);
+}
+
+#[test]
+fn test_rejected_withdrawal_summary() {
+    let mut analysis = Analysis::with_config(
+        AnalysisConfig::default()
+            .tx_uniqueness(TxUniqueness::PerClient)
+            .require_account_for_withdrawal(false),
+    );
+
+    let events = [deposit(1, 1, 10, "USD"), deposit(1, 2, 5, "EUR")];
+    for event in events.iter() {
+        assert_eq!(analysis.process_event(event), Ok(()));
+    }
+
+    let rows = |analysis: &Analysis| {
+        analysis
+            .current_summary(SummaryOrder::Client)
+            .map(|summary| (summary.client, summary.currency, summary.total()))
+            .collect::<Vec<_>>()
+    };
+    let before = analysis.clone();
+
+    let withdrawal = |client, tx, amount, code| Event::Withdrawal {
+        client: Client(client),
+        tx: Tx(tx),
+        amount: Amount::from(amount),
+        currency: currency(code),
+    };
+
+    // Currency new to the client
+    let result = analysis.process_event(&withdrawal(1, 3, 1, "GBP"));
+    assert_eq!(
+        result,
+        Err(AnalysisError::InsufficientFunds(
+            Client(1),
+            Tx(3),
+            Amount::from(1)
+        ))
+    );
+
+    // Duplicate transaction in a currency new to the client. Zero amount passes the funds check
+    let result = analysis.process_event(&withdrawal(1, 1, 0, "GBP"));
+    assert_eq!(result, Err(AnalysisError::DuplicateOperation(Tx(1))));
+
+    // Client without an account
+    let result = analysis.process_event(&withdrawal(2, 4, 1, "USD"));
+    assert_eq!(
+        result,
+        Err(AnalysisError::InsufficientFunds(
+            Client(2),
+            Tx(4),
+            Amount::from(1)
+        ))
+    );
+
+    assert_eq!(analysis, before);
+    assert_eq!(
+        rows(&analysis),
+        vec![
+            (Client(1), currency("EUR"), Amount::from(5)),
+            (Client(1), currency("USD"), Amount::from(10)),
+        ]
+    );
+    assert_eq!(analysis.account_summary(Client(1), currency("GBP")), None);
+    assert_eq!(analysis.account_summary(Client(2), currency("USD")), None);
+}
+
+#[test]
+fn test_snapshot_roundtrip() {
+    let mut analysis = Analysis::begin();
+
+    let events = [
+        deposit(1, 1, 10, "USD"),
+        deposit(1, 2, 5, "EUR"),
+        Event::Dispute {
+            client: Client(1),
+            tx: Tx(2),
+            currency: currency("EUR"),
+        },
+    ];
+
+    for event in events.iter() {
+        assert_eq!(analysis.process_event(event), Ok(()));
+    }
+
+    let mut snapshot = Vec::new();
+    analysis.snapshot(&mut snapshot).unwrap();
+
+    let restored: Analysis =
+        Analysis::restore(snapshot.as_slice(), AnalysisConfig::default()).unwrap();
+    assert_eq!(restored, analysis);
+    assert_eq!(
+        restored.operation(Client(1), Tx(2)).map(|op| op.currency),
+        Some(currency("EUR"))
+    );
+}
@@ -0,0 +1,127 @@
+use super::*;
+
+use pretty_assertions::assert_eq;
+
+#[test]
+fn test_wide_ids() {
+    let mut analysis = Analysis::<u64, u64>::new(AnalysisConfig::default());
+
+    let client = Client(u64::from(u16::MAX) + 1);
+    let tx = Tx(u64::from(u32::MAX) + 1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx,
+            amount: Amount::from(10),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Chargeback {
+            client,
+            tx,
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        assert_eq!(analysis.process_event(event), Ok(()));
+    }
+
+    assert_eq!(
+        analysis.account_summary(client, None),
+        Some(AccountSummary {
+            client,
+            currency: None,
+            available: Amount::from(0),
+            held: Amount::from(0),
+            locked: true,
+        })
+    );
+    assert_eq!(
+        analysis.process_event(&Event::Deposit {
+            client,
+            tx,
+            amount: Amount::from(1),
+            currency: None,
+        }),
+        Err(AnalysisError::AccountLocked(client))
+    );
+}
+
+#[test]
+fn test_string_ids() {
+    let mut analysis = Analysis::<String, u32>::new(AnalysisConfig::default());
+
+    let alice = || Client("alice".to_string());
+    let bob = || Client("bob".to_string());
+
+    let events = [
+        Event::Deposit {
+            client: bob(),
+            tx: Tx(1),
+            amount: Amount::from(3),
+            currency: None,
+        },
+        Event::Deposit {
+            client: alice(),
+            tx: Tx(2),
+            amount: Amount::from(5),
+            currency: None,
+        },
+        Event::Dispute {
+            client: alice(),
+            tx: Tx(2),
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        assert_eq!(analysis.process_event(event), Ok(()));
+    }
+
+    assert_eq!(
+        analysis.process_event(&Event::Withdrawal {
+            client: bob(),
+            tx: Tx(3),
+            amount: Amount::from(4),
+            currency: None,
+        }),
+        Err(AnalysisError::InsufficientFunds(
+            bob(),
+            Tx(3),
+            Amount::from(4)
+        ))
+    );
+    assert_eq!(
+        analysis.open_disputes().collect::<Vec<_>>(),
+        vec![(alice(), Tx(2))]
+    );
+
+    let mut snapshot = Vec::new();
+    analysis.snapshot(&mut snapshot).unwrap();
+    let restored = Analysis::restore(snapshot.as_slice(), AnalysisConfig::default()).unwrap();
+    assert_eq!(analysis, restored);
+
+    assert_eq!(
+        restored
+            .summary_ordered(SummaryOrder::Client)
+            .map(|summary| summary.client)
+            .collect::<Vec<_>>(),
+        vec![alice(), bob()]
+    );
+    assert_eq!(
+        analysis
+            .summary_ordered(SummaryOrder::Total)
+            .map(|summary| {
+                let total = summary.total();
+                (summary.client, total)
+            })
+            .collect::<Vec<_>>(),
+        vec![(bob(), Amount::from(3)), (alice(), Amount::from(5))]
+    );
+}
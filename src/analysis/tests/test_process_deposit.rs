@@ -1,4 +1,4 @@
-use super::operation::Kind as OperationKind;
+use super::operation::{Kind as OperationKind, State as OperationState};
 use super::*;
 
 #[test]
@@ -7,11 +7,16 @@ fn test_success() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
     utils::assert_account_not_exists(&analysis, client);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
@@ -26,10 +31,12 @@ fn test_success() {
         Operation {
             kind: OperationKind::Deposit,
             amount,
+            currency: None,
+            state: OperationState::Normal,
         },
     );
 
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
     utils::assert_account_exists(&analysis, client);
 }
 
@@ -39,9 +46,14 @@ fn test_failure_negative_amount_operation() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(-1.0);
+    let amount = Amount::from(-1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(
@@ -58,12 +70,17 @@ fn test_synthetic_failure_account_locked() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(-1.0);
+    let amount = Amount::from(-1);
 
     // Lock account
     analysis.locked_accounts.insert(client);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::AccountLocked(client)));
@@ -80,9 +97,14 @@ fn test_failure_duplicate_operations_same_account() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
@@ -94,17 +116,19 @@ fn test_failure_duplicate_operations_same_account() {
         Operation {
             kind: OperationKind::Deposit,
             amount,
+            currency: None,
+            state: OperationState::Normal,
         },
     );
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 
     // Repeat the same operation
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::DuplicateOperation(tx)));
 
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 }
 
 #[test]
@@ -113,9 +137,14 @@ fn test_failure_duplicate_operations_different_accounts() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
@@ -127,20 +156,111 @@ fn test_failure_duplicate_operations_different_accounts() {
         Operation {
             kind: OperationKind::Deposit,
             amount,
+            currency: None,
+            state: OperationState::Normal,
         },
     );
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::DuplicateOperation(tx)));
 
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
+}
+
+#[test]
+fn test_failure_amount_overflow() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+    let tx = Tx(1);
+    let amount = Amount::MAX;
+
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, client, Amount::MAX, Amount::from(0));
+
+    let tx = Tx(2);
+    let amount = Amount::from_scaled(1);
+
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::AmountOverflow(client, tx)));
+
+    utils::assert_operations_count(&analysis, client, 1);
+    utils::assert_account_balance(&analysis, client, Amount::MAX, Amount::from(0));
+}
+
+#[test]
+fn test_failure_amount_overflow_total() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+    let tx = Tx(1);
+    let amount = Amount::MAX;
+
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let event = Event::Dispute {
+        client,
+        tx,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::MAX);
+
+    // Available amount itself does not overflow but total amount does
+    let tx = Tx(2);
+    let amount = Amount::from(1);
+
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::AmountOverflow(client, tx)));
+
+    utils::assert_operations_count(&analysis, client, 1);
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::MAX);
 }
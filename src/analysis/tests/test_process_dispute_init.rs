@@ -7,30 +7,44 @@ fn test_success() {
     let client = Client(1);
 
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
     let tx = Tx(2);
-    let amount = Amount(2.0);
+    let amount = Amount::from(2);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(3.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(3), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 2);
 
-    let event = Event::Dispute { client, tx: Tx(1) };
+    let event = Event::Dispute {
+        client,
+        tx: Tx(1),
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(2.0), Amount(1.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(2), Amount::from(1));
     utils::assert_operations_count(&analysis, client, 2);
 
     utils::assert_disputes_count(&analysis, 1);
@@ -44,22 +58,31 @@ fn test_failure_dispute_already_in_progress() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 1);
 
-    let event = Event::Dispute { client, tx };
+    let event = Event::Dispute {
+        client,
+        tx,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(0.0), Amount(1.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(1));
     utils::assert_operations_count(&analysis, client, 1);
 
     utils::assert_disputes_count(&analysis, 1);
@@ -68,7 +91,7 @@ fn test_failure_dispute_already_in_progress() {
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::DisputeAlreadyInProgress(tx)));
 
-    utils::assert_account_balance(&analysis, client, Amount(0.0), Amount(1.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(1));
     utils::assert_operations_count(&analysis, client, 1);
 
     utils::assert_disputes_count(&analysis, 1);
@@ -81,25 +104,31 @@ fn test_failure_account_not_found() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 1);
 
     let event = Event::Dispute {
         client: Client(2),
         tx,
+        currency: None,
     };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::AccountNotFound(Client(2))));
 
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 1);
 
     utils::assert_disputes_count(&analysis, 0);
@@ -111,22 +140,31 @@ fn test_failure_operation_not_found() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 1);
 
-    let event = Event::Dispute { client, tx: Tx(2) };
+    let event = Event::Dispute {
+        client,
+        tx: Tx(2),
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::OperationNotFound(client, Tx(2))));
 
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 1);
 
     utils::assert_disputes_count(&analysis, 0);
@@ -139,35 +177,38 @@ fn test_failure_withdrawal_dispute_attempt() {
     let client = Client(1);
 
     let deposit_tx = Tx(1);
-    let amount = Amount(10.0);
+    let amount = Amount::from(10);
 
     let event = Event::Deposit {
         client,
         tx: deposit_tx,
         amount,
+        currency: None,
     };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
     let withdrawal_tx = Tx(2);
-    let amount = Amount(8.0);
+    let amount = Amount::from(8);
 
     let event = Event::Withdrawal {
         client,
         tx: withdrawal_tx,
         amount,
+        currency: None,
     };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(2.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(2), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 2);
 
     let event = Event::Dispute {
         client,
         tx: withdrawal_tx,
+        currency: None,
     };
 
     let result = analysis.process_event(&event);
@@ -179,7 +220,7 @@ fn test_failure_withdrawal_dispute_attempt() {
         ))
     );
 
-    utils::assert_account_balance(&analysis, client, Amount(2.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(2), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 2);
 
     utils::assert_disputes_count(&analysis, 0);
@@ -195,7 +236,11 @@ fn test_synthetic_failure_account_locked() {
     // Lock account
     analysis.locked_accounts.insert(client);
 
-    let event = Event::Dispute { client, tx };
+    let event = Event::Dispute {
+        client,
+        tx,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::AccountLocked(client)));
@@ -205,3 +250,154 @@ fn test_synthetic_failure_account_locked() {
     utils::assert_account_not_exists(&analysis, client);
     utils::assert_account_locked(&analysis, client);
 }
+
+#[test]
+fn test_failure_amount_overflow() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx: Tx(1),
+            amount: Amount::MAX,
+            currency: None,
+        },
+        Event::Withdrawal {
+            client,
+            tx: Tx(2),
+            amount: Amount::MAX,
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx: Tx(1),
+            currency: None,
+        },
+        Event::Deposit {
+            client,
+            tx: Tx(3),
+            amount: Amount::MAX,
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::MAX);
+    utils::assert_disputes_count(&analysis, 1);
+
+    // Held amount can't grow any further
+    let event = Event::Dispute {
+        client,
+        tx: Tx(3),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::AmountOverflow(client, Tx(3))));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::MAX);
+    utils::assert_disputes_count(&analysis, 1);
+    utils::assert_dispute_not_exists(&analysis, client, Tx(3));
+}
+
+#[test]
+fn test_success_redispute_after_resolve() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+    let tx = Tx(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx,
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Resolve {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(1));
+    utils::assert_dispute_exists(&analysis, client, tx);
+}
+
+#[test]
+fn test_failure_operation_charged_back() {
+    let mut analysis = Analysis::with_config(AnalysisConfig::default().lock_on_chargeback(false));
+
+    let client = Client(1);
+    let tx = Tx(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx,
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Chargeback {
+            client,
+            tx,
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    for event in [
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Resolve {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Chargeback {
+            client,
+            tx,
+            currency: None,
+        },
+    ] {
+        let result = analysis.process_event(&event);
+        assert_eq!(result, Err(AnalysisError::OperationChargedBack(client, tx)));
+    }
+
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(0));
+    utils::assert_disputes_count(&analysis, 0);
+}
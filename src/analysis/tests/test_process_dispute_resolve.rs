@@ -7,41 +7,59 @@ fn test_success() {
     let client = Client(1);
 
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
     let tx = Tx(2);
-    let amount = Amount(2.0);
+    let amount = Amount::from(2);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(3.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(3), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 2);
 
-    let event = Event::Dispute { client, tx: Tx(1) };
+    let event = Event::Dispute {
+        client,
+        tx: Tx(1),
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(2.0), Amount(1.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(2), Amount::from(1));
     utils::assert_operations_count(&analysis, client, 2);
 
     utils::assert_disputes_count(&analysis, 1);
     utils::assert_dispute_exists(&analysis, client, Tx(1));
 
-    let event = Event::Resolve { client, tx: Tx(1) };
+    let event = Event::Resolve {
+        client,
+        tx: Tx(1),
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(3.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(3), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 2);
 
     utils::assert_disputes_count(&analysis, 0);
@@ -54,30 +72,43 @@ fn test_failure_dipute_not_found_by_tx() {
     let client = Client(1);
 
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    let event = Event::Dispute { client, tx };
+    let event = Event::Dispute {
+        client,
+        tx,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(0.0), Amount(1.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(1));
     utils::assert_operations_count(&analysis, client, 1);
 
     utils::assert_disputes_count(&analysis, 1);
     utils::assert_dispute_exists(&analysis, client, Tx(1));
 
-    let event = Event::Resolve { client, tx: Tx(2) };
+    let event = Event::Resolve {
+        client,
+        tx: Tx(2),
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::DisputeNotFound(client, Tx(2))));
 
-    utils::assert_account_balance(&analysis, client, Amount(0.0), Amount(1.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(1));
     utils::assert_operations_count(&analysis, client, 1);
 
     utils::assert_disputes_count(&analysis, 1);
@@ -91,19 +122,28 @@ fn test_failure_dipute_not_found_by_client() {
     let client = Client(1);
 
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    let event = Event::Dispute { client, tx };
+    let event = Event::Dispute {
+        client,
+        tx,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(0.0), Amount(1.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(1));
     utils::assert_operations_count(&analysis, client, 1);
 
     utils::assert_disputes_count(&analysis, 1);
@@ -112,12 +152,13 @@ fn test_failure_dipute_not_found_by_client() {
     let event = Event::Resolve {
         client: Client(2),
         tx,
+        currency: None,
     };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::DisputeNotFound(Client(2), tx)));
 
-    utils::assert_account_balance(&analysis, client, Amount(0.0), Amount(1.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(1));
     utils::assert_operations_count(&analysis, client, 1);
 
     utils::assert_disputes_count(&analysis, 1);
@@ -131,12 +172,16 @@ fn test_synthetic_failure_account_not_found() {
     let client = Client(1);
     let tx = Tx(1);
 
-    assert!(analysis.disputes.insert(tx, client).is_none());
+    assert!(analysis.disputes.insert((client, tx)));
 
     utils::assert_disputes_count(&analysis, 1);
     utils::assert_dispute_exists(&analysis, client, tx);
 
-    let event = Event::Resolve { client, tx };
+    let event = Event::Resolve {
+        client,
+        tx,
+        currency: None,
+    };
 
     utils::assert_account_not_exists(&analysis, client);
 
@@ -156,27 +201,36 @@ fn test_synthetic_failure_operation_not_found() {
     let client = Client(1);
 
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 1);
 
-    assert!(analysis.disputes.insert(Tx(2), client).is_none());
+    assert!(analysis.disputes.insert((client, Tx(2))));
 
     utils::assert_disputes_count(&analysis, 1);
     utils::assert_dispute_exists(&analysis, client, Tx(2));
 
-    let event = Event::Resolve { client, tx: Tx(2) };
+    let event = Event::Resolve {
+        client,
+        tx: Tx(2),
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::OperationNotFound(client, Tx(2))));
 
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
     utils::assert_operations_count(&analysis, client, 1);
 
     utils::assert_disputes_count(&analysis, 1);
@@ -193,7 +247,11 @@ fn test_synthetic_failure_account_locked() {
     // Lock account
     analysis.locked_accounts.insert(client);
 
-    let event = Event::Resolve { client, tx };
+    let event = Event::Resolve {
+        client,
+        tx,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::AccountLocked(client)));
@@ -204,3 +262,62 @@ fn test_synthetic_failure_account_locked() {
     utils::assert_account_locked(&analysis, client);
     utils::assert_disputes_count(&analysis, 0);
 }
+
+#[test]
+fn test_failure_dispute_already_resolved() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+    let tx = Tx(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx,
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Resolve {
+            client,
+            tx,
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    let event = Event::Resolve {
+        client,
+        tx,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(
+        result,
+        Err(AnalysisError::DisputeAlreadyResolved(client, tx))
+    );
+
+    let event = Event::Chargeback {
+        client,
+        tx,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(
+        result,
+        Err(AnalysisError::DisputeAlreadyResolved(client, tx))
+    );
+
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
+    utils::assert_disputes_count(&analysis, 0);
+}
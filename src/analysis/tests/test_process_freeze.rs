@@ -0,0 +1,103 @@
+use super::*;
+
+#[test]
+fn test_success() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+
+    let event = Event::Deposit {
+        client,
+        tx: Tx(1),
+        amount: Amount::from(1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let event = Event::Freeze {
+        client,
+        reason: "suspected fraud".to_string(),
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_locked(&analysis, client);
+
+    // Locked account rejects further operations
+    let event = Event::Deposit {
+        client,
+        tx: Tx(2),
+        amount: Amount::from(1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::AccountLocked(client)));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
+}
+
+#[test]
+fn test_failure_account_not_found() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+
+    let event = Event::Freeze {
+        client,
+        reason: "suspected fraud".to_string(),
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::AccountNotFound(client)));
+
+    utils::assert_account_not_exists(&analysis, client);
+    utils::assert_account_not_locked(&analysis, client);
+}
+
+#[test]
+fn test_failure_account_already_locked() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+    let tx = Tx(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx,
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Chargeback {
+            client,
+            tx,
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    utils::assert_account_locked(&analysis, client);
+
+    let event = Event::Freeze {
+        client,
+        reason: "suspected fraud".to_string(),
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::AccountAlreadyLocked(client)));
+
+    utils::assert_account_locked(&analysis, client);
+}
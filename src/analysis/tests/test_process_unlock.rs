@@ -0,0 +1,125 @@
+use super::*;
+
+#[test]
+fn test_success() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+    let tx = Tx(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx,
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Deposit {
+            client,
+            tx: Tx(2),
+            amount: Amount::from(2),
+            currency: None,
+        },
+        Event::Dispute {
+            client,
+            tx,
+            currency: None,
+        },
+        Event::Chargeback {
+            client,
+            tx,
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    utils::assert_account_locked(&analysis, client);
+
+    let event = Event::Unlock {
+        client,
+        reason: "chargeback reviewed".to_string(),
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_not_locked(&analysis, client);
+
+    // Unlocked account accepts operations again
+    let event = Event::Withdrawal {
+        client,
+        tx: Tx(3),
+        amount: Amount::from(2),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(0));
+}
+
+#[test]
+fn test_failure_account_not_locked() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+
+    let event = Event::Deposit {
+        client,
+        tx: Tx(1),
+        amount: Amount::from(1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let event = Event::Unlock {
+        client,
+        reason: "chargeback reviewed".to_string(),
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::AccountNotLocked(client)));
+
+    utils::assert_account_not_locked(&analysis, client);
+}
+
+#[test]
+fn test_failure_freeze_unlock_unlock() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+
+    let events = [
+        Event::Deposit {
+            client,
+            tx: Tx(1),
+            amount: Amount::from(1),
+            currency: None,
+        },
+        Event::Freeze {
+            client,
+            reason: "suspected fraud".to_string(),
+        },
+        Event::Unlock {
+            client,
+            reason: "false alarm".to_string(),
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    let result = analysis.process_event(&events[2]);
+    assert_eq!(result, Err(AnalysisError::AccountNotLocked(client)));
+
+    utils::assert_account_not_locked(&analysis, client);
+}
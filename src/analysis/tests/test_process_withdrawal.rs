@@ -1,4 +1,4 @@
-use super::operation::Kind as OperationKind;
+use super::operation::{Kind as OperationKind, State as OperationState};
 use super::*;
 
 #[test]
@@ -7,12 +7,17 @@ fn test_success() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
     utils::assert_account_not_exists(&analysis, client);
 
     // A deposit with sufficient amount should occur before a withdrawal
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
@@ -24,18 +29,25 @@ fn test_success() {
         Operation {
             kind: OperationKind::Deposit,
             amount,
+            currency: None,
+            state: OperationState::Normal,
         },
     );
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 
     let client = Client(1);
     let tx = Tx(2);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
     utils::assert_account_exists(&analysis, client);
 
-    let event = Event::Withdrawal { client, tx, amount };
+    let event = Event::Withdrawal {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
@@ -47,10 +59,12 @@ fn test_success() {
         Operation {
             kind: OperationKind::Withdrawal,
             amount,
+            currency: None,
+            state: OperationState::Normal,
         },
     );
     utils::assert_operations_count(&analysis, client, 2);
-    utils::assert_account_balance(&analysis, client, Amount(0.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(0));
 }
 
 #[test]
@@ -59,12 +73,17 @@ fn test_failure_account_not_found() {
 
     let client = Client(1);
     let tx = Tx(2);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
     utils::assert_account_not_exists(&analysis, client);
 
     // Withdrawal can happen only if account record has previously been created
-    let event = Event::Withdrawal { client, tx, amount };
+    let event = Event::Withdrawal {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::AccountNotFound(client)));
@@ -79,20 +98,30 @@ fn test_failure_negative_amount_operations() {
     let client = Client(1);
 
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 
     let tx = Tx(2);
-    let amount = Amount(-1.0);
+    let amount = Amount::from(-1);
 
-    let event = Event::Withdrawal { client, tx, amount };
+    let event = Event::Withdrawal {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(
@@ -101,7 +130,7 @@ fn test_failure_negative_amount_operations() {
     );
 
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 }
 
 #[test]
@@ -111,20 +140,30 @@ fn test_failure_insufficient_funds() {
     let client = Client(1);
 
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 
     let tx = Tx(2);
-    let amount = Amount(1.5);
+    let amount = Amount::from_scaled(15_000);
 
-    let event = Event::Withdrawal { client, tx, amount };
+    let event = Event::Withdrawal {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(
@@ -133,7 +172,7 @@ fn test_failure_insufficient_funds() {
     );
 
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 }
 
 #[test]
@@ -142,12 +181,17 @@ fn test_synthetic_failure_account_locked() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(-1.0);
+    let amount = Amount::from(-1);
 
     // Lock account
     analysis.locked_accounts.insert(client);
 
-    let event = Event::Withdrawal { client, tx, amount };
+    let event = Event::Withdrawal {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::AccountLocked(client)));
@@ -164,9 +208,14 @@ fn test_failure_duplicate_operations_same_account() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
@@ -178,19 +227,26 @@ fn test_failure_duplicate_operations_same_account() {
         Operation {
             kind: OperationKind::Deposit,
             amount,
+            currency: None,
+            state: OperationState::Normal,
         },
     );
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 
     // Emit event with same tx
-    let event = Event::Withdrawal { client, tx, amount };
+    let event = Event::Withdrawal {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::DuplicateOperation(tx)));
 
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 }
 
 #[test]
@@ -199,37 +255,89 @@ fn test_failure_duplicate_operations_different_accounts() {
 
     let client = Client(1);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 
     let client = Client(2);
     let tx = Tx(2);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Ok(()));
 
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
 
     let client = Client(2);
     let tx = Tx(1);
-    let amount = Amount(1.0);
+    let amount = Amount::from(1);
 
-    let event = Event::Deposit { client, tx, amount };
+    let event = Event::Deposit {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
 
     let result = analysis.process_event(&event);
     assert_eq!(result, Err(AnalysisError::DuplicateOperation(tx)));
 
     utils::assert_operations_count(&analysis, client, 1);
-    utils::assert_account_balance(&analysis, client, Amount(1.0), Amount(0.0));
+    utils::assert_account_balance(&analysis, client, Amount::from(1), Amount::from(0));
+}
+
+#[test]
+fn test_success_exact_arithmetic() {
+    let mut analysis = Analysis::begin();
+
+    let client = Client(1);
+
+    for (tx, amount) in [(Tx(1), "0.1"), (Tx(2), "0.2")] {
+        let amount = amount.parse().unwrap();
+        let event = Event::Deposit {
+            client,
+            tx,
+            amount,
+            currency: None,
+        };
+
+        let result = analysis.process_event(&event);
+        assert_eq!(result, Ok(()));
+    }
+
+    // 0.1 + 0.2 is exactly 0.3, so the whole balance can be withdrawn
+    let tx = Tx(3);
+    let amount = "0.3".parse().unwrap();
+
+    let event = Event::Withdrawal {
+        client,
+        tx,
+        amount,
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_operations_count(&analysis, client, 3);
+    utils::assert_account_balance(&analysis, client, Amount::from(0), Amount::from(0));
 }
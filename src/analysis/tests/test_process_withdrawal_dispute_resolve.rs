@@ -0,0 +1,161 @@
+use super::*;
+
+#[test]
+fn test_success_hold() {
+    let mut analysis = utils::begin_with_withdrawal_dispute_policy(WithdrawalDisputePolicy::Hold);
+
+    let client = Client(1);
+
+    let event = Event::Deposit {
+        client,
+        tx: Tx(1),
+        amount: Amount::from(3),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let event = Event::Withdrawal {
+        client,
+        tx: Tx(2),
+        amount: Amount::from(1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let event = Event::Dispute {
+        client,
+        tx: Tx(2),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(2), Amount::from(1));
+
+    let event = Event::Resolve {
+        client,
+        tx: Tx(2),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    // Withdrawal stands. Held amount is released
+    utils::assert_account_balance(&analysis, client, Amount::from(2), Amount::from(0));
+    utils::assert_operations_count(&analysis, client, 2);
+
+    utils::assert_disputes_count(&analysis, 0);
+}
+
+#[test]
+fn test_success_provisional_credit() {
+    let mut analysis =
+        utils::begin_with_withdrawal_dispute_policy(WithdrawalDisputePolicy::ProvisionalCredit);
+
+    let client = Client(1);
+
+    let event = Event::Deposit {
+        client,
+        tx: Tx(1),
+        amount: Amount::from(3),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let event = Event::Withdrawal {
+        client,
+        tx: Tx(2),
+        amount: Amount::from(1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let event = Event::Dispute {
+        client,
+        tx: Tx(2),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, client, Amount::from(3), Amount::from(0));
+
+    let event = Event::Resolve {
+        client,
+        tx: Tx(2),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    // Withdrawal stands. Provisional credit is taken back
+    utils::assert_account_balance(&analysis, client, Amount::from(2), Amount::from(0));
+    utils::assert_operations_count(&analysis, client, 2);
+
+    utils::assert_disputes_count(&analysis, 0);
+}
+
+#[test]
+fn test_failure_dipute_not_found_by_client() {
+    let mut analysis = utils::begin_with_withdrawal_dispute_policy(WithdrawalDisputePolicy::Hold);
+
+    let client = Client(1);
+
+    let event = Event::Deposit {
+        client,
+        tx: Tx(1),
+        amount: Amount::from(3),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let event = Event::Withdrawal {
+        client,
+        tx: Tx(2),
+        amount: Amount::from(1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let event = Event::Dispute {
+        client,
+        tx: Tx(2),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    let event = Event::Resolve {
+        client: Client(2),
+        tx: Tx(2),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(
+        result,
+        Err(AnalysisError::DisputeNotFound(Client(2), Tx(2)))
+    );
+
+    utils::assert_account_balance(&analysis, client, Amount::from(2), Amount::from(1));
+
+    utils::assert_disputes_count(&analysis, 1);
+    utils::assert_dispute_exists(&analysis, client, Tx(2));
+}
@@ -0,0 +1,106 @@
+use super::*;
+
+use std::collections::HashSet;
+
+#[test]
+fn test_query() {
+    let mut analysis = Analysis::begin();
+
+    let events = [
+        Event::Deposit {
+            client: Client(1),
+            tx: Tx(1),
+            amount: Amount::from(10),
+            currency: None,
+        },
+        Event::Withdrawal {
+            client: Client(1),
+            tx: Tx(2),
+            amount: Amount::from(3),
+            currency: None,
+        },
+        Event::Dispute {
+            client: Client(1),
+            tx: Tx(1),
+            currency: None,
+        },
+        Event::Deposit {
+            client: Client(2),
+            tx: Tx(3),
+            amount: Amount::from(5),
+            currency: None,
+        },
+        Event::Dispute {
+            client: Client(2),
+            tx: Tx(3),
+            currency: None,
+        },
+        Event::Chargeback {
+            client: Client(2),
+            tx: Tx(3),
+            currency: None,
+        },
+    ];
+
+    for event in events.iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    let account = analysis.account(Client(1)).unwrap();
+    assert_eq!(account.client(), Client(1));
+    assert_eq!(account.available(), Amount::from(-3));
+    assert_eq!(account.held(), Amount::from(10));
+    assert_eq!(account.total(), Amount::from(7));
+    assert!(!account.is_locked());
+    assert_eq!(account.operations().count(), 2);
+
+    assert!(analysis.account(Client(3)).is_none());
+
+    assert!(!analysis.is_locked(Client(1)));
+    assert!(analysis.is_locked(Client(2)));
+    assert!(!analysis.is_locked(Client(3)));
+
+    assert_eq!(
+        analysis.operation(Client(1), Tx(1)),
+        Some(OperationView {
+            tx: Tx(1),
+            kind: OperationKind::Deposit,
+            amount: Amount::from(10),
+            currency: None,
+            state: OperationState::Disputed,
+        })
+    );
+    assert_eq!(
+        analysis.operation(Client(1), Tx(2)),
+        Some(OperationView {
+            tx: Tx(2),
+            kind: OperationKind::Withdrawal,
+            amount: Amount::from(3),
+            currency: None,
+            state: OperationState::Normal,
+        })
+    );
+    assert_eq!(
+        analysis
+            .operation(Client(2), Tx(3))
+            .map(|operation| operation.state),
+        Some(OperationState::ChargedBack)
+    );
+    assert_eq!(analysis.operation(Client(2), Tx(1)), None);
+
+    assert_eq!(
+        analysis.open_disputes().collect::<Vec<_>>(),
+        vec![(Client(1), Tx(1))]
+    );
+
+    assert_eq!(
+        analysis
+            .iter_accounts()
+            .map(|account| (account.client(), account.is_locked()))
+            .collect::<HashSet<_>>(),
+        [(Client(1), false), (Client(2), true)]
+            .into_iter()
+            .collect::<HashSet<_>>()
+    );
+}
@@ -0,0 +1,229 @@
+use super::*;
+
+use assert_matches::assert_matches;
+
+fn events() -> Vec<Event> {
+    vec![
+        Event::Deposit {
+            client: Client(1),
+            tx: Tx(1),
+            amount: Amount::from(10),
+            currency: None,
+        },
+        Event::Deposit {
+            client: Client(1),
+            tx: Tx(2),
+            amount: Amount::from(5),
+            currency: None,
+        },
+        Event::Dispute {
+            client: Client(1),
+            tx: Tx(2),
+            currency: None,
+        },
+        Event::Deposit {
+            client: Client(2),
+            tx: Tx(3),
+            amount: Amount::from(3),
+            currency: None,
+        },
+        Event::Dispute {
+            client: Client(2),
+            tx: Tx(3),
+            currency: None,
+        },
+        Event::Chargeback {
+            client: Client(2),
+            tx: Tx(3),
+            currency: None,
+        },
+    ]
+}
+
+fn snapshot(analysis: &Analysis) -> Vec<u8> {
+    let mut snapshot = Vec::new();
+    analysis.snapshot(&mut snapshot).unwrap();
+    snapshot
+}
+
+#[test]
+fn test_roundtrip() {
+    let mut analysis = Analysis::begin();
+
+    for event in events().iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    let restored = Analysis::restore(snapshot(&analysis).as_slice(), AnalysisConfig::default());
+    assert_eq!(restored.unwrap(), analysis);
+}
+
+#[test]
+fn test_deterministic() {
+    let mut analysis = Analysis::begin();
+
+    for event in events().iter() {
+        let result = analysis.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    let restored =
+        Analysis::restore(snapshot(&analysis).as_slice(), AnalysisConfig::default()).unwrap();
+    assert_eq!(snapshot(&restored), snapshot(&analysis));
+}
+
+#[test]
+fn test_continue_processing() {
+    let events = events();
+    let (before, after) = events.split_at(3);
+
+    let mut expected = Analysis::begin();
+    for event in events.iter() {
+        let _ = expected.process_event(event);
+    }
+
+    let mut analysis = Analysis::begin();
+    for event in before.iter() {
+        let _ = analysis.process_event(event);
+    }
+
+    let mut analysis =
+        Analysis::restore(snapshot(&analysis).as_slice(), AnalysisConfig::default()).unwrap();
+    for event in after.iter() {
+        let _ = analysis.process_event(event);
+    }
+
+    assert_eq!(analysis, expected);
+
+    // Restored state is enforced: tx 1 has been used before the snapshot
+    let event = Event::Deposit {
+        client: Client(3),
+        tx: Tx(1),
+        amount: Amount::from(1),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Err(AnalysisError::DuplicateOperation(Tx(1))));
+
+    let event = Event::Resolve {
+        client: Client(1),
+        tx: Tx(2),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, Client(1), Amount::from(15), Amount::from(0));
+    utils::assert_account_locked(&analysis, Client(2));
+}
+
+#[test]
+fn test_config_not_saved() {
+    let analysis = Analysis::with_config(AnalysisConfig::default().allow_zero_amounts(false));
+
+    let config = AnalysisConfig::default().lock_on_chargeback(false);
+    let restored: Analysis =
+        Analysis::restore(snapshot(&analysis).as_slice(), config.clone()).unwrap();
+
+    assert_eq!(restored.config(), &config);
+}
+
+#[test]
+fn test_restore_version_1() {
+    // State after `events()` as written before balances were tracked per currency
+    let snapshot_v1 = br#"{
+        "version": 1,
+        "accounts": [
+            {
+                "client": 1,
+                "available": "10.0000",
+                "held": "5.0000",
+                "operations": [
+                    {"tx": 1, "kind": "deposit", "amount": "10.0000", "state": "normal"},
+                    {"tx": 2, "kind": "deposit", "amount": "5.0000", "state": "disputed"}
+                ]
+            },
+            {
+                "client": 2,
+                "available": "0.0000",
+                "held": "0.0000",
+                "operations": [
+                    {"tx": 3, "kind": "deposit", "amount": "3.0000", "state": "charged_back"}
+                ]
+            }
+        ],
+        "disputes": [{"client": 1, "tx": 2}],
+        "locked_accounts": [2],
+        "used_txs": [1, 2, 3]
+    }"#;
+
+    let mut expected = Analysis::begin();
+    for event in events().iter() {
+        let result = expected.process_event(event);
+        assert_eq!(result, Ok(()));
+    }
+
+    let mut analysis = Analysis::restore(&snapshot_v1[..], AnalysisConfig::default()).unwrap();
+    assert_eq!(analysis, expected);
+
+    // Restored snapshot is taken in the current format
+    let restored =
+        Analysis::restore(snapshot(&analysis).as_slice(), AnalysisConfig::default()).unwrap();
+    assert_eq!(restored, expected);
+
+    let event = Event::Resolve {
+        client: Client(1),
+        tx: Tx(2),
+        currency: None,
+    };
+
+    let result = analysis.process_event(&event);
+    assert_eq!(result, Ok(()));
+
+    utils::assert_account_balance(&analysis, Client(1), Amount::from(15), Amount::from(0));
+}
+
+#[test]
+fn test_failure_unsupported_version() {
+    let snapshot = br#"{"version":3,"accounts":{}}"#;
+
+    let result = Analysis::<u16, u32>::restore(&snapshot[..], AnalysisConfig::default());
+    assert_matches!(result, Err(SnapshotError::UnsupportedVersion(3)));
+
+    let snapshot = br#"{"version":0,"accounts":{}}"#;
+
+    let result = Analysis::<u16, u32>::restore(&snapshot[..], AnalysisConfig::default());
+    assert_matches!(result, Err(SnapshotError::UnsupportedVersion(0)));
+}
+
+#[test]
+fn test_failure_malformed() {
+    let snapshot = br#"{"version":2,"accounts":[]}"#;
+
+    let result = Analysis::<u16, u32>::restore(&snapshot[..], AnalysisConfig::default());
+    assert_matches!(result, Err(SnapshotError::Malformed(_)));
+
+    let result = Analysis::<u16, u32>::restore(&b"not a snapshot"[..], AnalysisConfig::default());
+    assert_matches!(result, Err(SnapshotError::Malformed(_)));
+}
+
+#[test]
+fn test_failure_inconsistent() {
+    let snapshot = br#"{
+        "version": 2,
+        "accounts": [{
+            "client": 1,
+            "balances": [{"currency": null, "available": "1.0000", "held": "0.0000"}],
+            "operations": [{"tx": 1, "kind": "deposit", "amount": "1.0000", "currency": null, "state": "normal"}]
+        }],
+        "disputes": [{"client": 1, "tx": 1}],
+        "locked_accounts": [],
+        "used_txs": [1]
+    }"#;
+
+    let result = Analysis::<u16, u32>::restore(&snapshot[..], AnalysisConfig::default());
+    assert_matches!(result, Err(SnapshotError::Inconsistent(_)));
+}
@@ -2,34 +2,129 @@
 pub mod raw;
 /// provides wrappers for primitives used in [Event]
 pub mod wrappers;
-pub use raw::RawEvent;
+pub use raw::{Error as ParseEventError, ParseConfig, RawEvent};
 
-use wrappers::{Amount, Client, Tx};
+use wrappers::{Amount, Client, Currency, Id, Tx};
 
 use serde::{Deserialize, Serialize};
 
-/// Represents all possible interactions of a client with the payment system
-#[derive(Debug, Serialize, Deserialize)]
+/// Represents all possible interactions of a client with the payment system.
+///
+/// Generic over the underlying types of [client][Client] and [transaction][Tx] IDs.
+/// Default ones are `u16` and `u32` respectively.
+///
+/// Transactional events optionally specify a [currency][Currency]. Events without one
+/// relate to the client's balance without a currency (the only one of single-currency logs)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
-pub enum Event {
+pub enum Event<C = u16, T = u32> {
     /// Chargeback as a result of a dispute
-    Chargeback { client: Client, tx: Tx },
+    Chargeback {
+        client: Client<C>,
+        tx: Tx<T>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        currency: Option<Currency>,
+    },
     /// Deposit transaction
     Deposit {
-        client: Client,
-        tx: Tx,
+        client: Client<C>,
+        tx: Tx<T>,
         amount: Amount,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        currency: Option<Currency>,
+    },
+    /// Dispute init. Currency must match the one of the disputed operation
+    Dispute {
+        client: Client<C>,
+        tx: Tx<T>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        currency: Option<Currency>,
     },
-    /// Dispute init
-    Dispute { client: Client, tx: Tx },
     /// Dispute resolve
-    Resolve { client: Client, tx: Tx },
+    Resolve {
+        client: Client<C>,
+        tx: Tx<T>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        currency: Option<Currency>,
+    },
     /// Deposit transaction
     Withdrawal {
-        client: Client,
-        tx: Tx,
+        client: Client<C>,
+        tx: Tx<T>,
         amount: Amount,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        currency: Option<Currency>,
     },
+    /// Administrative account lock
+    Freeze { client: Client<C>, reason: String },
+    /// Administrative account unlock
+    Unlock { client: Client<C>, reason: String },
+}
+
+impl<C: Id, T: Id> Event<C, T> {
+    /// Returns [type][EventType] of the event
+    pub fn ty(&self) -> EventType {
+        match self {
+            Self::Chargeback { .. } => EventType::Chargeback,
+            Self::Deposit { .. } => EventType::Deposit,
+            Self::Dispute { .. } => EventType::Dispute,
+            Self::Resolve { .. } => EventType::Resolve,
+            Self::Withdrawal { .. } => EventType::Withdrawal,
+            Self::Freeze { .. } => EventType::Freeze,
+            Self::Unlock { .. } => EventType::Unlock,
+        }
+    }
+
+    /// Returns [client ID][Client] the event relates to
+    pub fn client(&self) -> Client<C> {
+        match self {
+            Self::Chargeback { client, .. }
+            | Self::Deposit { client, .. }
+            | Self::Dispute { client, .. }
+            | Self::Resolve { client, .. }
+            | Self::Withdrawal { client, .. }
+            | Self::Freeze { client, .. }
+            | Self::Unlock { client, .. } => client.clone(),
+        }
+    }
+
+    /// Returns [transaction ID][Tx] the event relates to.
+    /// Administrative events do not relate to any transaction
+    pub fn tx(&self) -> Option<Tx<T>> {
+        match self {
+            Self::Chargeback { tx, .. }
+            | Self::Deposit { tx, .. }
+            | Self::Dispute { tx, .. }
+            | Self::Resolve { tx, .. }
+            | Self::Withdrawal { tx, .. } => Some(tx.clone()),
+            Self::Freeze { .. } | Self::Unlock { .. } => None,
+        }
+    }
+
+    /// Returns [currency][Currency] of the event (if it has one).
+    /// Administrative events do not relate to any currency
+    pub fn currency(&self) -> Option<Currency> {
+        match self {
+            Self::Chargeback { currency, .. }
+            | Self::Deposit { currency, .. }
+            | Self::Dispute { currency, .. }
+            | Self::Resolve { currency, .. }
+            | Self::Withdrawal { currency, .. } => *currency,
+            Self::Freeze { .. } | Self::Unlock { .. } => None,
+        }
+    }
+
+    /// Returns [amount][Amount] of the event (if it has one)
+    pub fn amount(&self) -> Option<Amount> {
+        match self {
+            Self::Deposit { amount, .. } | Self::Withdrawal { amount, .. } => Some(*amount),
+            Self::Chargeback { .. }
+            | Self::Dispute { .. }
+            | Self::Resolve { .. }
+            | Self::Freeze { .. }
+            | Self::Unlock { .. } => None,
+        }
+    }
 }
 
 /// Utility list of all [event][Event] types
@@ -41,4 +136,6 @@ pub enum EventType {
     Dispute,
     Resolve,
     Withdrawal,
+    Freeze,
+    Unlock,
 }
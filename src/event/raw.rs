@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 
 use super::{
-    wrappers::{Amount, Client, Tx},
+    wrappers::{Amount, Client, Currency, Id, ParseAmountError, ParseCurrencyError, Rounding, Tx},
     Event, EventType,
 };
 
@@ -10,95 +13,493 @@ use super::{
 ///
 /// One of the cases is limitations of the [csv] library to deserialize
 /// internally tagged enums ([issue](https://github.com/BurntSushi/rust-csv/issues/211))
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-pub struct RawEvent {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawEvent<C = u16, T = u32> {
     #[serde(rename = "type")]
     ty: EventType,
-    client: Client,
-    tx: Tx,
-    amount: Option<Amount>,
+    client: Client<C>,
+    /// Absent for administrative events
+    tx: Option<Tx<T>>,
+    /// Kept textual so that it is validated against [ParseConfig] during conversion
+    amount: Option<String>,
+    /// Audit reason of administrative events.
+    /// The column is optional and can be omitted from input altogether
+    #[serde(default)]
+    reason: Option<String>,
+    /// Currency of transactional events.
+    /// The column is optional and can be omitted from input altogether
+    #[serde(default)]
+    currency: Option<String>,
 }
 
-impl TryFrom<RawEvent> for Event {
-    type Error = eyre::Report;
+/// Error of [RawEvent] to [Event] conversion.
+/// Raised when a field required by the event type is missing
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum Error<C = u16, T = u32> {
+    /// Transactional event has no transaction ID
+    #[error("{ty:?} has no 'tx' specified (client: {client})")]
+    MissingTx { ty: EventType, client: Client<C> },
+    /// Deposit or withdrawal has no amount
+    #[error("{ty:?} has no 'amount' specified (client: {client}, tx: {tx})")]
+    MissingAmount {
+        ty: EventType,
+        client: Client<C>,
+        tx: Tx<T>,
+    },
+    /// Administrative event has no audit reason
+    #[error("{ty:?} has no 'reason' specified (client: {client})")]
+    MissingReason { ty: EventType, client: Client<C> },
+    /// Field is given but not used by the event type (like `amount` of a dispute).
+    /// Raised only if [ParseConfig::reject_unexpected_fields] is set
+    #[error("{ty:?} has unexpected '{field}' specified: {value:?} (client: {client})")]
+    UnexpectedField {
+        ty: EventType,
+        client: Client<C>,
+        tx: Option<Tx<T>>,
+        field: &'static str,
+        /// Field value as given in input
+        value: String,
+    },
+    /// Amount is not a valid decimal number (like `NaN` or `inf`),
+    /// does not fit into [Amount] or is more precise than allowed
+    #[error("{ty:?} has invalid amount (client: {client}, tx: {tx}): {source}")]
+    InvalidAmount {
+        ty: EventType,
+        client: Client<C>,
+        tx: Tx<T>,
+        #[source]
+        source: ParseAmountError,
+    },
+    /// Currency is not a valid [currency code][Currency]
+    #[error("{ty:?} has invalid currency (client: {client}): {source}")]
+    InvalidCurrency {
+        ty: EventType,
+        client: Client<C>,
+        #[source]
+        source: ParseCurrencyError,
+    },
+    /// Amount magnitude is above [ParseConfig::max_amount]
+    #[error("{ty:?} amount {amount} is above maximum {max} (client: {client}, tx: {tx})")]
+    AmountAboveMaximum {
+        ty: EventType,
+        client: Client<C>,
+        tx: Tx<T>,
+        amount: Amount,
+        max: Amount,
+        /// Amount as given in input
+        input: String,
+    },
+}
 
-    fn try_from(raw: RawEvent) -> eyre::Result<Event> {
-        match raw.ty {
-            EventType::Chargeback => Ok(Self::Chargeback {
-                client: raw.client,
-                tx: raw.tx,
-            }),
-            EventType::Dispute => Ok(Self::Dispute {
-                client: raw.client,
-                tx: raw.tx,
-            }),
-            EventType::Resolve => Ok(Self::Resolve {
-                client: raw.client,
-                tx: raw.tx,
-            }),
-            EventType::Deposit => {
-                let amount = raw.amount.ok_or_else(|| {
-                    eyre::eyre!("Deposit has no 'amount' specified: (tx: {})", raw.tx.0)
+impl<C: Id, T: Id> Error<C, T> {
+    /// Stable machine-readable error code
+    ///
+    /// | Code                     | Error                                         |
+    /// |--------------------------|-----------------------------------------------|
+    /// | `E_MISSING_TX` | [MissingTx][Error::MissingTx] |
+    /// | `E_MISSING_AMOUNT` | [MissingAmount][Error::MissingAmount] |
+    /// | `E_MISSING_REASON` | [MissingReason][Error::MissingReason] |
+    /// | `E_UNEXPECTED_FIELD` | [UnexpectedField][Error::UnexpectedField] |
+    /// | `E_INVALID_AMOUNT` | [InvalidAmount][Error::InvalidAmount] |
+    /// | `E_INVALID_CURRENCY` | [InvalidCurrency][Error::InvalidCurrency] |
+    /// | `E_AMOUNT_ABOVE_MAXIMUM` | [AmountAboveMaximum][Error::AmountAboveMaximum] |
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MissingTx { .. } => "E_MISSING_TX",
+            Self::MissingAmount { .. } => "E_MISSING_AMOUNT",
+            Self::MissingReason { .. } => "E_MISSING_REASON",
+            Self::UnexpectedField { .. } => "E_UNEXPECTED_FIELD",
+            Self::InvalidAmount { .. } => "E_INVALID_AMOUNT",
+            Self::InvalidCurrency { .. } => "E_INVALID_CURRENCY",
+            Self::AmountAboveMaximum { .. } => "E_AMOUNT_ABOVE_MAXIMUM",
+        }
+    }
+
+    /// Type of the invalid event
+    pub fn ty(&self) -> EventType {
+        match self {
+            Self::MissingTx { ty, .. }
+            | Self::MissingAmount { ty, .. }
+            | Self::MissingReason { ty, .. }
+            | Self::UnexpectedField { ty, .. }
+            | Self::InvalidAmount { ty, .. }
+            | Self::InvalidCurrency { ty, .. }
+            | Self::AmountAboveMaximum { ty, .. } => *ty,
+        }
+    }
+
+    /// Client of the invalid event. Always known, [Option] is for the sake of
+    /// the same shape as [tx][Error::tx] (and [AnalysisError::client][crate::AnalysisError::client])
+    pub fn client(&self) -> Option<&Client<C>> {
+        match self {
+            Self::MissingTx { client, .. }
+            | Self::MissingAmount { client, .. }
+            | Self::MissingReason { client, .. }
+            | Self::UnexpectedField { client, .. }
+            | Self::InvalidAmount { client, .. }
+            | Self::InvalidCurrency { client, .. }
+            | Self::AmountAboveMaximum { client, .. } => Some(client),
+        }
+    }
+
+    /// Transaction ID of the invalid event if it is known at the point of failure
+    pub fn tx(&self) -> Option<&Tx<T>> {
+        match self {
+            Self::MissingAmount { tx, .. }
+            | Self::InvalidAmount { tx, .. }
+            | Self::AmountAboveMaximum { tx, .. } => Some(tx),
+            Self::UnexpectedField { tx, .. } => tx.as_ref(),
+            Self::MissingTx { .. } | Self::MissingReason { .. } | Self::InvalidCurrency { .. } => {
+                None
+            }
+        }
+    }
+}
+
+/// Validation rules of [RawEvent] to [Event] conversion.
+/// Default configuration accepts every amount representable by [Amount]
+///
+/// Configuration is built by chaining setters on top of the default one:
+/// ```
+/// use scoring::event::{wrappers::{Amount, Rounding}, ParseConfig};
+///
+/// let config = ParseConfig::default()
+///     .max_amount(Amount::from(1_000_000))
+///     .rounding(Rounding::Reject);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseConfig {
+    /// Largest accepted amount magnitude (default: [Amount::MAX])
+    pub max_amount: Amount,
+    /// Handling of amounts with more than [Amount::DECIMAL_PLACES] fractional digits
+    /// (default: [Rounding::HalfAwayFromZero])
+    pub rounding: Rounding,
+    /// Whether fields not used by the event type (see [RawEvent::unexpected_fields])
+    /// are rejected rather than ignored (default: `false`)
+    pub reject_unexpected_fields: bool,
+}
+
+impl Default for ParseConfig {
+    fn default() -> Self {
+        Self {
+            max_amount: Amount::MAX,
+            rounding: Rounding::default(),
+            reject_unexpected_fields: false,
+        }
+    }
+}
+
+impl ParseConfig {
+    /// Sets [ParseConfig::max_amount]
+    pub fn max_amount(mut self, max: Amount) -> Self {
+        self.max_amount = max;
+        self
+    }
+
+    /// Sets [ParseConfig::rounding]
+    pub fn rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Sets [ParseConfig::reject_unexpected_fields]
+    pub fn reject_unexpected_fields(mut self, reject: bool) -> Self {
+        self.reject_unexpected_fields = reject;
+        self
+    }
+}
+
+impl<C: Id, T: Id> RawEvent<C, T> {
+    /// Deserializes event from a JSON document. Unlike CSV, JSON has numbers,
+    /// so `amount` may be either a string or a number. A number is taken as its literal
+    /// (in plain decimal notation if it has an exponent), so it is validated during conversion
+    /// exactly like a string is.
+    ///
+    /// Deserializing a number into the textual amount is not left to serde:
+    /// [csv] infers field types that way and would read every decimal amount through f64.
+    /// Neither is [serde_json::Value], which keeps numbers as f64 and rounds long literals
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let fields: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str::<HashMap<String, &RawValue>>(json)?
+                .into_iter()
+                .map(|(name, raw)| {
+                    let value = match (name.as_str(), raw.get().as_bytes()[0]) {
+                        ("amount", b'-' | b'0'..=b'9') => {
+                            serde_json::Value::String(plain_decimal(raw.get()))
+                        }
+                        _ => serde_json::from_str(raw.get())?,
+                    };
+                    Ok((name, value))
+                })
+                .collect::<serde_json::Result<_>>()?;
+
+        Self::deserialize(serde_json::Value::Object(fields))
+    }
+
+    /// Returns fields which are given but not used by the event type:
+    /// `tx` and `currency` of administrative events, `amount` of events other than
+    /// deposits and withdrawals and `reason` of events other than administrative ones
+    pub fn unexpected_fields(&self) -> Vec<&'static str> {
+        // Currency is used by the same events as transaction ID
+        let (tx, amount, reason) = match self.ty {
+            EventType::Deposit | EventType::Withdrawal => (true, true, false),
+            EventType::Dispute | EventType::Resolve | EventType::Chargeback => (true, false, false),
+            EventType::Freeze | EventType::Unlock => (false, false, true),
+        };
+
+        let mut fields = Vec::new();
+        if !tx && self.tx.is_some() {
+            fields.push("tx");
+        }
+        if !amount && self.amount.is_some() {
+            fields.push("amount");
+        }
+        if !reason && self.reason.is_some() {
+            fields.push("reason");
+        }
+        if !tx && self.currency.is_some() {
+            fields.push("currency");
+        }
+
+        fields
+    }
+
+    /// Value of the optional `field` (`tx`, `amount`, `reason` or `currency`)
+    /// as given in input. `None` if the field is absent or unknown
+    pub fn value(&self, field: &str) -> Option<String> {
+        match field {
+            "tx" => self.tx.as_ref().map(Tx::to_string),
+            "amount" => self.amount.clone(),
+            "reason" => self.reason.clone(),
+            "currency" => self.currency.clone(),
+            _ => None,
+        }
+    }
+
+    /// Converts into [Event] validating it against `config`.
+    /// [TryFrom] conversion follows the default [ParseConfig]
+    pub fn into_event(self, config: &ParseConfig) -> Result<Event<C, T>, Error<C, T>> {
+        if config.reject_unexpected_fields {
+            if let Some(field) = self.unexpected_fields().first().copied() {
+                return Err(Error::UnexpectedField {
+                    value: self.value(field).unwrap_or_default(),
+                    ty: self.ty,
+                    client: self.client,
+                    tx: self.tx,
+                    field,
+                });
+            }
+        }
+
+        let RawEvent {
+            ty,
+            client,
+            tx,
+            amount,
+            reason,
+            currency,
+        } = self;
+        // IDs are cloned only on failure
+        let tx = |client: &Client<C>| {
+            tx.ok_or_else(|| Error::MissingTx {
+                ty,
+                client: client.clone(),
+            })
+        };
+        let amount = |client: &Client<C>, tx: &Tx<T>| {
+            let input = amount.as_deref().ok_or_else(|| Error::MissingAmount {
+                ty,
+                client: client.clone(),
+                tx: tx.clone(),
+            })?;
+            let amount =
+                Amount::parse(input, config.rounding).map_err(|source| Error::InvalidAmount {
+                    ty,
+                    client: client.clone(),
+                    tx: tx.clone(),
+                    source,
                 })?;
 
-                Ok(Self::Deposit {
-                    client: raw.client,
-                    tx: raw.tx,
+            // Negative maximum makes no sense and is treated as zero
+            let max = config.max_amount.max(Amount::ZERO);
+            if amount > max || amount < -max {
+                return Err(Error::AmountAboveMaximum {
+                    ty,
+                    client: client.clone(),
+                    tx: tx.clone(),
+                    amount,
+                    max,
+                    input: input.to_string(),
+                });
+            }
+
+            Ok(amount)
+        };
+        let currency = |client: &Client<C>| {
+            currency
+                .as_deref()
+                .map(str::parse)
+                .transpose()
+                .map_err(|source| Error::InvalidCurrency {
+                    ty,
+                    client: client.clone(),
+                    source,
+                })
+        };
+        let reason = |client: &Client<C>| {
+            reason.ok_or_else(|| Error::MissingReason {
+                ty,
+                client: client.clone(),
+            })
+        };
+
+        match ty {
+            EventType::Chargeback => {
+                let tx = tx(&client)?;
+                let currency = currency(&client)?;
+
+                Ok(Event::Chargeback {
+                    client,
+                    tx,
+                    currency,
+                })
+            }
+            EventType::Dispute => {
+                let tx = tx(&client)?;
+                let currency = currency(&client)?;
+
+                Ok(Event::Dispute {
+                    client,
+                    tx,
+                    currency,
+                })
+            }
+            EventType::Resolve => {
+                let tx = tx(&client)?;
+                let currency = currency(&client)?;
+
+                Ok(Event::Resolve {
+                    client,
+                    tx,
+                    currency,
+                })
+            }
+            EventType::Deposit => {
+                let tx = tx(&client)?;
+                let amount = amount(&client, &tx)?;
+                let currency = currency(&client)?;
+
+                Ok(Event::Deposit {
+                    client,
+                    tx,
                     amount,
+                    currency,
                 })
             }
             EventType::Withdrawal => {
-                let amount = raw.amount.ok_or_else(|| {
-                    eyre::eyre!("Withdrawal has no 'amount' specified: (tx: {})", raw.tx.0)
-                })?;
+                let tx = tx(&client)?;
+                let amount = amount(&client, &tx)?;
+                let currency = currency(&client)?;
 
-                Ok(Self::Withdrawal {
-                    client: raw.client,
-                    tx: raw.tx,
+                Ok(Event::Withdrawal {
+                    client,
+                    tx,
                     amount,
+                    currency,
                 })
             }
+            EventType::Freeze => {
+                let reason = reason(&client)?;
+
+                Ok(Event::Freeze { client, reason })
+            }
+            EventType::Unlock => {
+                let reason = reason(&client)?;
+
+                Ok(Event::Unlock { client, reason })
+            }
         }
     }
 }
 
-impl From<Event> for RawEvent {
-    fn from(event: Event) -> Self {
-        match event {
-            Event::Chargeback { client, tx } => Self {
-                ty: EventType::Chargeback,
-                client,
-                tx,
-                amount: None,
-            },
-            Event::Dispute { client, tx } => Self {
-                ty: EventType::Dispute,
-                client,
-                tx,
-                amount: None,
-            },
-            Event::Resolve { client, tx } => Self {
-                ty: EventType::Resolve,
-                client,
-                tx,
-                amount: None,
-            },
-            Event::Deposit { client, tx, amount } => Self {
-                ty: EventType::Deposit,
-                client,
-                tx,
-                amount: Some(amount),
-            },
-            Event::Withdrawal { client, tx, amount } => Self {
-                ty: EventType::Withdrawal,
-                client,
-                tx,
-                amount: Some(amount),
-            },
+impl<C: Id, T: Id> TryFrom<RawEvent<C, T>> for Event<C, T> {
+    type Error = Error<C, T>;
+
+    fn try_from(raw: RawEvent<C, T>) -> Result<Self, Self::Error> {
+        raw.into_event(&ParseConfig::default())
+    }
+}
+
+impl<C: Id, T: Id> From<Event<C, T>> for RawEvent<C, T> {
+    fn from(event: Event<C, T>) -> Self {
+        let mut raw = Self {
+            ty: event.ty(),
+            client: event.client(),
+            tx: event.tx(),
+            amount: event.amount().as_ref().map(Amount::to_string),
+            reason: None,
+            currency: event.currency().as_ref().map(Currency::to_string),
+        };
+
+        if let Event::Freeze { reason, .. } | Event::Unlock { reason, .. } = event {
+            raw.reason = Some(reason);
         }
+
+        raw
+    }
+}
+
+/// Writes JSON number `literal` in plain decimal notation (without an exponent)
+/// keeping every digit of it.
+///
+/// Exponents are clamped: the digits are moved at most [PLAIN_DECIMAL_MAX_SHIFT] places
+/// off the decimal point. Such values are far out of [Amount] range or far below its
+/// precision, so the clamping changes neither the result of the parsing nor its error
+fn plain_decimal(literal: &str) -> String {
+    let (mantissa, exponent) = match literal.split_once(['e', 'E']) {
+        Some(parts) => parts,
+        None => return literal.to_string(),
+    };
+
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let digits = format!("{}{}", integer, fraction);
+    let significant = digits.trim_start_matches('0');
+    if significant.is_empty() {
+        return "0".to_string();
+    }
+
+    // Exponent too long for i64 is far beyond the clamping bounds anyway
+    let exponent = exponent
+        .parse::<i64>()
+        .unwrap_or(match exponent.starts_with('-') {
+            true => i64::MIN,
+            false => i64::MAX,
+        });
+    // Position of the decimal point relative to the first significant digit
+    let point = (integer.len() as i64 - (digits.len() - significant.len()) as i64)
+        .saturating_add(exponent)
+        .clamp(-PLAIN_DECIMAL_MAX_SHIFT, PLAIN_DECIMAL_MAX_SHIFT);
+
+    if point <= 0 {
+        format!("{}0.{}{}", sign, "0".repeat(-point as usize), significant)
+    } else if point as usize >= significant.len() {
+        let zeros = "0".repeat(point as usize - significant.len());
+        format!("{}{}{}", sign, significant, zeros)
+    } else {
+        let (integer, fraction) = significant.split_at(point as usize);
+        format!("{}{}.{}", sign, integer, fraction)
     }
 }
 
+/// See [plain_decimal]
+const PLAIN_DECIMAL_MAX_SHIFT: i64 = 32;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,16 +533,516 @@ withdrawal,1,2,1.0
                 RawEvent {
                     ty: EventType::Deposit,
                     client: Client(1),
-                    tx: Tx(1),
-                    amount: Some(Amount(2.0))
+                    tx: Some(Tx(1)),
+                    amount: Some("2.0".to_string()),
+                    reason: None,
+                    currency: None
                 },
                 RawEvent {
                     ty: EventType::Withdrawal,
                     client: Client(1),
-                    tx: Tx(2),
-                    amount: Some(Amount(1.0))
+                    tx: Some(Tx(2)),
+                    amount: Some("1.0".to_string()),
+                    reason: None,
+                    currency: None
                 }
             ]
         );
     }
+
+    #[test]
+    fn test_csv_deserialize_amount() {
+        let data = r#"
+type,client,tx,amount
+deposit,1,1,0.12345
+deposit,1,2,1e3
+        "#;
+
+        let mut reader = csv::Reader::from_reader(data.trim().as_bytes());
+        let mut events = reader
+            .deserialize::<RawEvent>()
+            .map(|raw| Event::try_from(raw.unwrap()));
+
+        assert_matches!(
+            events.next(),
+            Some(Ok(Event::Deposit { amount, .. })) if amount == Amount::from_scaled(1235)
+        );
+        assert_matches!(
+            events.next(),
+            Some(Err(Error::InvalidAmount {
+                source: ParseAmountError::Invalid(_),
+                ..
+            }))
+        );
+    }
+
+    #[test]
+    fn test_json_deserialize_amount() {
+        let parse = |json| {
+            RawEvent::<u16, u32>::from_json(json)
+                .unwrap()
+                .into_event(&ParseConfig::default())
+        };
+
+        assert_matches!(
+            parse(r#"{"type":"deposit","client":1,"tx":1,"amount":"1.5"}"#),
+            Ok(Event::Deposit { amount, .. }) if amount == Amount::from_scaled(15_000)
+        );
+        assert_matches!(
+            parse(r#"{"type":"deposit","client":1,"tx":1,"amount":2}"#),
+            Ok(Event::Deposit { amount, .. }) if amount == Amount::from(2)
+        );
+        assert_matches!(
+            parse(r#"{"type":"withdrawal","client":1,"tx":1,"amount":-3}"#),
+            Ok(Event::Withdrawal { amount, .. }) if amount == Amount::from(-3)
+        );
+        assert_matches!(
+            parse(r#"{"type":"deposit","client":1,"tx":1,"amount":1.5}"#),
+            Ok(Event::Deposit { amount, .. }) if amount == Amount::from_scaled(15_000)
+        );
+        assert_matches!(
+            parse(r#"{"type":"deposit","client":1,"tx":1,"amount":0.00001}"#),
+            Ok(Event::Deposit { amount, .. }) if amount == Amount::ZERO
+        );
+        assert_matches!(
+            parse(r#"{"type":"deposit","client":1,"tx":1,"amount":1e20}"#),
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::OutOfRange(_),
+                ..
+            })
+        );
+        assert_matches!(
+            parse(r#"{"type":"deposit","client":1,"tx":1,"amount":18446744073709551615}"#),
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::OutOfRange(_),
+                ..
+            })
+        );
+
+        // Validation against the config applies to numbers too
+        let config = ParseConfig::default()
+            .rounding(Rounding::Reject)
+            .max_amount(Amount::from(10));
+        let parse = |json| {
+            RawEvent::<u16, u32>::from_json(json)
+                .unwrap()
+                .into_event(&config)
+        };
+
+        assert_matches!(
+            parse(r#"{"type":"deposit","client":1,"tx":1,"amount":0.12345}"#),
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::TooPrecise(_),
+                ..
+            })
+        );
+        assert_matches!(
+            parse(r#"{"type":"deposit","client":1,"tx":1,"amount":11}"#),
+            Err(Error::AmountAboveMaximum { .. })
+        );
+
+        assert!(RawEvent::<u16, u32>::from_json(
+            r#"{"type":"deposit","client":1,"tx":1,"amount":true}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_json_deserialize_precise_amount() {
+        let parse = |json, rounding| {
+            RawEvent::<u16, u32>::from_json(json)
+                .unwrap()
+                .into_event(&ParseConfig::default().rounding(rounding))
+        };
+
+        // More significant digits than f64 keeps
+        let json = r#"{"type":"deposit","client":1,"tx":1,"amount":12345678901234.5678}"#;
+        for rounding in [Rounding::HalfAwayFromZero, Rounding::Reject] {
+            assert_matches!(
+                parse(json, rounding),
+                Ok(Event::Deposit { amount, .. }) if amount == Amount::from_scaled(123_456_789_012_345_678)
+            );
+        }
+
+        // Nearest f64 would be rounded up
+        let json = r#"{"type":"deposit","client":1,"tx":1,"amount":0.12344999999999999999}"#;
+        assert_matches!(
+            parse(json, Rounding::HalfAwayFromZero),
+            Ok(Event::Deposit { amount, .. }) if amount == Amount::from_scaled(1_234)
+        );
+        assert_matches!(
+            parse(json, Rounding::Reject),
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::TooPrecise(_),
+                ..
+            })
+        );
+
+        // Nearest f64 would be taken as a 4 decimal places amount
+        let json = r#"{"type":"deposit","client":1,"tx":1,"amount":0.1234999999999999999}"#;
+        assert_matches!(
+            parse(json, Rounding::HalfAwayFromZero),
+            Ok(Event::Deposit { amount, .. }) if amount == Amount::from_scaled(1_235)
+        );
+        assert_matches!(
+            parse(json, Rounding::Reject),
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::TooPrecise(_),
+                ..
+            })
+        );
+
+        let json = r#"{"type":"deposit","client":1,"tx":1,"amount":1.5e-3}"#;
+        assert_matches!(
+            parse(json, Rounding::Reject),
+            Ok(Event::Deposit { amount, .. }) if amount == Amount::from_scaled(15)
+        );
+        let json = r#"{"type":"deposit","client":1,"tx":1,"amount":1e-400}"#;
+        assert_matches!(
+            parse(json, Rounding::HalfAwayFromZero),
+            Ok(Event::Deposit { amount, .. }) if amount == Amount::ZERO
+        );
+        assert_matches!(
+            parse(json, Rounding::Reject),
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::TooPrecise(_),
+                ..
+            })
+        );
+        let json = r#"{"type":"deposit","client":1,"tx":1,"amount":1e400}"#;
+        assert_matches!(
+            parse(json, Rounding::HalfAwayFromZero),
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::OutOfRange(_),
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn test_plain_decimal() {
+        assert_eq!(plain_decimal("12.50"), "12.50");
+        assert_eq!(
+            plain_decimal("-0.1234999999999999999"),
+            "-0.1234999999999999999"
+        );
+        assert_eq!(plain_decimal("1.5e-3"), "0.0015");
+        assert_eq!(plain_decimal("-15E2"), "-1500");
+        assert_eq!(plain_decimal("0.0125e+2"), "1.25");
+        assert_eq!(plain_decimal("12345e-2"), "123.45");
+        assert_eq!(plain_decimal("0e10"), "0");
+        assert_eq!(plain_decimal("1e-400"), format!("0.{}1", "0".repeat(32)));
+        assert_eq!(
+            plain_decimal("1e99999999999999999999"),
+            format!("1{}", "0".repeat(31))
+        );
+    }
+
+    #[test]
+    fn test_unexpected_fields() {
+        let data = r#"
+type,client,tx,amount,reason
+dispute,1,1,1.0,
+resolve,1,1,,
+chargeback,1,1,,fraud
+freeze,1,1,,fraud
+deposit,1,2,1.0,
+        "#;
+
+        let mut reader = csv::Reader::from_reader(data.trim().as_bytes());
+        let raws = reader
+            .deserialize::<RawEvent>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            raws.iter()
+                .map(RawEvent::unexpected_fields)
+                .collect::<Vec<_>>(),
+            vec![vec!["amount"], vec![], vec!["reason"], vec!["tx"], vec![]]
+        );
+        assert_eq!(raws[0].value("amount").as_deref(), Some("1.0"));
+        assert_eq!(raws[2].value("reason").as_deref(), Some("fraud"));
+        assert_eq!(raws[3].value("tx").as_deref(), Some("1"));
+        assert_eq!(raws[1].value("amount"), None);
+
+        // Unexpected fields are ignored by default
+        let lenient = ParseConfig::default();
+        let strict = ParseConfig::default().reject_unexpected_fields(true);
+
+        assert_matches!(
+            raws[0].clone().into_event(&lenient),
+            Ok(Event::Dispute {
+                client: Client(1),
+                tx: Tx(1),
+                currency: None
+            })
+        );
+        assert_eq!(
+            raws[0].clone().into_event(&strict),
+            Err(Error::UnexpectedField {
+                ty: EventType::Dispute,
+                client: Client(1),
+                tx: Some(Tx(1)),
+                field: "amount",
+                value: "1.0".to_string()
+            })
+        );
+        assert_matches!(
+            raws[1].clone().into_event(&strict),
+            Ok(Event::Resolve { .. })
+        );
+        assert_matches!(
+            raws[2].clone().into_event(&strict),
+            Err(Error::UnexpectedField {
+                field: "reason",
+                ..
+            })
+        );
+        assert_matches!(
+            raws[3].clone().into_event(&strict),
+            Err(Error::UnexpectedField { field: "tx", .. })
+        );
+        assert_matches!(
+            raws[4].clone().into_event(&strict),
+            Ok(Event::Deposit { .. })
+        );
+    }
+
+    #[test]
+    fn test_amount_validation() {
+        let data = r#"
+type,client,tx,amount
+deposit,1,1,NaN
+deposit,1,2,inf
+withdrawal,1,3,1e308
+deposit,1,4,1000.0001
+withdrawal,1,5,-1000.0001
+deposit,1,6,0.00001
+deposit,1,7,1000.0000
+        "#;
+
+        let config = ParseConfig::default()
+            .max_amount(Amount::from(1000))
+            .rounding(Rounding::Reject);
+
+        let mut reader = csv::Reader::from_reader(data.trim().as_bytes());
+        let events = reader
+            .deserialize::<RawEvent>()
+            .map(|raw| raw.unwrap().into_event(&config))
+            .collect::<Vec<_>>();
+
+        assert_matches!(
+            &events[0],
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::Invalid(_),
+                tx: Tx(1),
+                ..
+            })
+        );
+        assert_matches!(
+            &events[1],
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::Invalid(_),
+                tx: Tx(2),
+                ..
+            })
+        );
+        assert_matches!(
+            &events[2],
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::Invalid(_),
+                tx: Tx(3),
+                ..
+            })
+        );
+        assert_matches!(
+            &events[3],
+            Err(Error::AmountAboveMaximum { tx: Tx(4), max, .. }) if *max == Amount::from(1000)
+        );
+        assert_matches!(
+            &events[4],
+            Err(Error::AmountAboveMaximum { tx: Tx(5), input, .. }) if input == "-1000.0001"
+        );
+        assert_matches!(
+            &events[5],
+            Err(Error::InvalidAmount {
+                source: ParseAmountError::TooPrecise(_),
+                tx: Tx(6),
+                ..
+            })
+        );
+        assert_matches!(&events[6], Ok(Event::Deposit { amount, .. }) if *amount == Amount::from(1000));
+
+        // Default configuration accepts everything representable
+        assert_matches!(
+            Event::try_from(RawEvent {
+                ty: EventType::Deposit,
+                client: Client(1),
+                tx: Some(Tx(1)),
+                amount: Some("922337203685477.5807".to_string()),
+                reason: None,
+                currency: None,
+            }),
+            Ok(Event::Deposit {
+                amount: Amount::MAX,
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn test_csv_deserialize_admin_events() {
+        let data = r#"
+type,client,tx,amount,reason
+freeze,1,,,suspected fraud
+unlock,1,,,"checked, all good"
+unlock,1,,,
+        "#;
+
+        let mut reader = csv::Reader::from_reader(data.trim().as_bytes());
+        let events = reader
+            .deserialize::<RawEvent>()
+            .map(|raw| Event::try_from(raw.unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_matches!(
+            &events[0],
+            Ok(Event::Freeze { client: Client(1), reason }) if reason == "suspected fraud"
+        );
+        assert_matches!(
+            &events[1],
+            Ok(Event::Unlock { client: Client(1), reason }) if reason == "checked, all good"
+        );
+        assert_matches!(
+            &events[2],
+            Err(Error::MissingReason {
+                ty: EventType::Unlock,
+                client: Client(1)
+            })
+        );
+    }
+
+    #[test]
+    fn test_csv_deserialize_missing_fields() {
+        let data = r#"
+type,client,tx,amount
+deposit,1,,1.0
+withdrawal,1,2,
+dispute,1,,
+        "#;
+
+        let mut reader = csv::Reader::from_reader(data.trim().as_bytes());
+        let events = reader
+            .deserialize::<RawEvent>()
+            .map(|raw| Event::try_from(raw.unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            events,
+            vec![
+                Err(Error::MissingTx {
+                    ty: EventType::Deposit,
+                    client: Client(1)
+                }),
+                Err(Error::MissingAmount {
+                    ty: EventType::Withdrawal,
+                    client: Client(1),
+                    tx: Tx(2)
+                }),
+                Err(Error::MissingTx {
+                    ty: EventType::Dispute,
+                    client: Client(1)
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_code() {
+        let err = Error::<u16, u32>::MissingAmount {
+            ty: EventType::Withdrawal,
+            client: Client(1),
+            tx: Tx(2),
+        };
+        assert_eq!(err.code(), "E_MISSING_AMOUNT");
+        assert_eq!(err.ty(), EventType::Withdrawal);
+        assert_eq!(err.client(), Some(&Client(1)));
+        assert_eq!(err.tx(), Some(&Tx(2)));
+
+        let err = Error::<u16, u32>::UnexpectedField {
+            ty: EventType::Dispute,
+            client: Client(3),
+            tx: Some(Tx(4)),
+            field: "amount",
+            value: "1".to_string(),
+        };
+        assert_eq!(err.code(), "E_UNEXPECTED_FIELD");
+        assert_eq!(err.tx(), Some(&Tx(4)));
+
+        let err = Error::<u16, u32>::MissingReason {
+            ty: EventType::Freeze,
+            client: Client(5),
+        };
+        assert_eq!(err.code(), "E_MISSING_REASON");
+        assert_eq!(err.tx(), None);
+    }
+
+    #[test]
+    fn test_csv_deserialize_currency() {
+        let data = r#"
+type,client,tx,amount,currency,reason
+deposit,1,1,1.0,usd,
+withdrawal,1,2,1.0,,
+dispute,1,1,,USD,
+deposit,1,3,1.0,US D,
+freeze,1,,,EUR,fraud
+        "#;
+
+        let mut reader = csv::Reader::from_reader(data.trim().as_bytes());
+        let raws = reader
+            .deserialize::<RawEvent>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let usd = "USD".parse::<Currency>().unwrap();
+
+        assert_eq!(
+            raws[0].clone().into_event(&ParseConfig::default()),
+            Ok(Event::Deposit {
+                client: Client(1),
+                tx: Tx(1),
+                amount: Amount::from(1),
+                currency: Some(usd)
+            })
+        );
+        assert_matches!(
+            raws[1].clone().into_event(&ParseConfig::default()),
+            Ok(Event::Withdrawal { currency: None, .. })
+        );
+        assert_eq!(
+            raws[2].clone().into_event(&ParseConfig::default()),
+            Ok(Event::Dispute {
+                client: Client(1),
+                tx: Tx(1),
+                currency: Some(usd)
+            })
+        );
+        assert_matches!(
+            raws[3].clone().into_event(&ParseConfig::default()),
+            Err(Error::InvalidCurrency {
+                ty: EventType::Deposit,
+                source: ParseCurrencyError::Invalid(_),
+                ..
+            })
+        );
+        assert_eq!(raws[4].unexpected_fields(), vec!["currency"]);
+        assert_matches!(
+            raws[4].clone().into_event(&ParseConfig::default()),
+            Ok(Event::Freeze { .. })
+        );
+
+        // Currency survives the roundtrip through the intermediate representation
+        let event = raws[0].clone().into_event(&ParseConfig::default()).unwrap();
+        assert_eq!(Event::try_from(RawEvent::from(event.clone())), Ok(event));
+    }
 }
@@ -1,25 +1,242 @@
 use std::{
     fmt,
     ops::{Add, AddAssign, Neg, SubAssign},
+    str::FromStr,
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
-/// Amount. Several mathematical operations are implemented for this
-/// wrapper type for convenience.
-#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
-pub struct Amount(pub f64);
+/// Amount. Represented as a fixed-point decimal number with
+/// [Amount::DECIMAL_PLACES] decimal places (internally - a number of
+/// ten-thousandths). This way arithmetic on amounts is exact.
+///
+/// Several mathematical operations are implemented for this
+/// wrapper type for convenience. **Note that operators panic on overflow.**
+/// Use [Amount::checked_add] and [Amount::checked_sub] where overflow
+/// can not be ruled out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i64);
+
+/// Policy applied when a parsed amount has more than
+/// [Amount::DECIMAL_PLACES] fractional digits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round to the nearest representable amount.
+    /// Ties are rounded away from zero (0.00005 -> 0.0001)
+    #[default]
+    HalfAwayFromZero,
+    /// Reject the amount with [ParseAmountError::TooPrecise]
+    Reject,
+}
+
+/// Error which can occur when parsing an [Amount]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseAmountError {
+    /// Input is not a plain decimal number (like `1`, `-0.5` or `.25`)
+    #[error("invalid amount: {0:?}")]
+    Invalid(String),
+    /// Input has more fractional digits than supported
+    /// and [Rounding::Reject] policy is in effect
+    #[error(
+        "amount has more than {} decimal places: {0:?}",
+        Amount::DECIMAL_PLACES
+    )]
+    TooPrecise(String),
+    /// Input does not fit into [Amount]
+    #[error("amount out of range: {0:?}")]
+    OutOfRange(String),
+}
+
+impl ParseAmountError {
+    /// Input which failed to parse
+    pub fn input(&self) -> &str {
+        match self {
+            Self::Invalid(input) | Self::TooPrecise(input) | Self::OutOfRange(input) => input,
+        }
+    }
+}
+
+impl Amount {
+    /// Number of decimal places amount keeps
+    pub const DECIMAL_PLACES: u32 = 4;
+    /// Number of units in a single whole unit of currency
+    pub const SCALE: i64 = 10_i64.pow(Self::DECIMAL_PLACES);
+
+    /// Zero amount
+    pub const ZERO: Self = Self(0);
+    /// Largest representable amount
+    pub const MAX: Self = Self(i64::MAX);
+    /// Smallest representable amount
+    pub const MIN: Self = Self(i64::MIN);
+
+    /// Constructs amount from a number of ten-thousandths
+    /// (`Amount::from_scaled(15_000)` is `1.5`)
+    pub const fn from_scaled(units: i64) -> Self {
+        Self(units)
+    }
+
+    /// Returns amount as a number of ten-thousandths
+    pub const fn scaled(self) -> i64 {
+        self.0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+
+    /// Returns `None` on overflow
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    /// Returns `None` on overflow
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    /// Parses plain decimal notation (optional sign, integer part,
+    /// optional fractional part). Exponents, `inf` and `NaN` are not accepted.
+    /// Extra fractional digits are handled according to `rounding`.
+    pub fn parse(s: &str, rounding: Rounding) -> Result<Self, ParseAmountError> {
+        let invalid = || ParseAmountError::Invalid(s.to_string());
+
+        let (negative, digits) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+
+        let (integer, fraction) = match digits.split_once('.') {
+            Some((integer, fraction)) => (integer, fraction),
+            None => (digits, ""),
+        };
+
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+
+        if !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let places = Self::DECIMAL_PLACES as usize;
+        let (kept, extra) = fraction.split_at(fraction.len().min(places));
+
+        if rounding == Rounding::Reject && extra.bytes().any(|b| b != b'0') {
+            return Err(ParseAmountError::TooPrecise(s.to_string()));
+        }
+
+        let out_of_range = || ParseAmountError::OutOfRange(s.to_string());
+
+        // Accumulate as negative number so that `Amount::MIN` is parseable
+        let mut units: i64 = 0;
+        for digit in integer.bytes().chain(kept.bytes()) {
+            units = units
+                .checked_mul(10)
+                .and_then(|units| units.checked_sub(i64::from(digit - b'0')))
+                .ok_or_else(out_of_range)?;
+        }
+        for _ in kept.len()..places {
+            units = units.checked_mul(10).ok_or_else(out_of_range)?;
+        }
+
+        if rounding == Rounding::HalfAwayFromZero && extra.bytes().next() >= Some(b'5') {
+            units = units.checked_sub(1).ok_or_else(out_of_range)?;
+        }
+
+        if negative {
+            Ok(Self(units))
+        } else {
+            units.checked_neg().map(Self).ok_or_else(out_of_range)
+        }
+    }
+}
+
+impl From<i32> for Amount {
+    /// Converts whole units of currency into amount
+    fn from(whole: i32) -> Self {
+        Self(i64::from(whole) * Self::SCALE)
+    }
+}
+
+impl FromStr for Amount {
+    type Err = ParseAmountError;
+
+    /// Parses amount applying [Rounding::HalfAwayFromZero] policy
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, Rounding::default())
+    }
+}
 
 impl fmt::Display for Amount {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Amount is displayed with fixed 4 decimal places
-        write!(f, "{:.4}", self.0)
+        let sign = if self.is_negative() { "-" } else { "" };
+        let units = self.0.unsigned_abs();
+        let scale = Self::SCALE.unsigned_abs();
+
+        write!(
+            f,
+            "{}{}.{:0width$}",
+            sign,
+            units / scale,
+            units % scale,
+            width = Self::DECIMAL_PLACES as usize
+        )
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Amount;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a decimal number string or an integer")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Amount, E> {
+                s.parse().map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Amount, E> {
+                v.checked_mul(Amount::SCALE)
+                    .map(Amount)
+                    .ok_or_else(|| E::custom(ParseAmountError::OutOfRange(v.to_string())))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Amount, E> {
+                match i64::try_from(v) {
+                    Ok(v) => self.visit_i64(v),
+                    Err(_) => Err(E::custom(ParseAmountError::OutOfRange(v.to_string()))),
+                }
+            }
+        }
+
+        // Amounts are read from their textual representation. Integers (as in JSON) are accepted
+        // too since they are exact. Fractional numbers are not: the deserializer has already
+        // rounded them to f64, so the digits they were written with are lost.
+        // See [RawEvent::from_json][crate::event::raw::RawEvent::from_json] for JSON input
+        // with fractional numbers
+        deserializer.deserialize_any(Visitor)
     }
 }
 
 impl AddAssign for Amount {
     fn add_assign(&mut self, other: Self) {
-        *self = Self(self.0 + other.0);
+        *self = *self + other;
     }
 }
 
@@ -27,7 +244,7 @@ impl Neg for Amount {
     type Output = Self;
 
     fn neg(self) -> Self::Output {
-        Self(-self.0)
+        Self(self.0.checked_neg().expect("amount overflow"))
     }
 }
 
@@ -35,19 +252,13 @@ impl Add for Amount {
     type Output = Self;
 
     fn add(self, other: Self) -> Self::Output {
-        Self(self.0 + other.0)
+        self.checked_add(other).expect("amount overflow")
     }
 }
 
 impl SubAssign for Amount {
     fn sub_assign(&mut self, other: Self) {
-        *self = Self(self.0 - other.0);
-    }
-}
-
-impl Amount {
-    pub fn is_negative(&self) -> bool {
-        self.0 < 0.0
+        *self = self.checked_sub(other).expect("amount overflow");
     }
 }
 
@@ -55,55 +266,159 @@ impl Amount {
 mod tests {
     use super::*;
 
+    use assert_matches::*;
+
     #[test]
     fn test_add() {
-        let a = Amount(10.0);
-        let b = Amount(5.0);
+        let a = Amount::from(10);
+        let b = Amount::from(5);
 
-        assert_eq!(Amount(15.0), a + b);
+        assert_eq!(Amount::from(15), a + b);
     }
 
     #[test]
     fn test_add_assign() {
-        let mut a = Amount(10.0);
-        let b = Amount(5.0);
+        let mut a = Amount::from(10);
+        let b = Amount::from(5);
 
         a += b;
 
-        assert_eq!(Amount(15.0), a);
+        assert_eq!(Amount::from(15), a);
     }
 
     #[test]
     fn test_sub_assign() {
-        let mut a = Amount(10.0);
-        let b = Amount(5.0);
+        let mut a = Amount::from(10);
+        let b = Amount::from(5);
 
         a -= b;
 
-        assert_eq!(Amount(5.0), a);
+        assert_eq!(Amount::from(5), a);
     }
 
     #[test]
     fn test_neg() {
-        let a = Amount(10.0);
+        let a = Amount::from(10);
+
+        assert_eq!(Amount::from(-10), -a);
+    }
+
+    #[test]
+    fn test_checked() {
+        assert_eq!(Amount::MAX.checked_add(Amount::from_scaled(1)), None);
+        assert_eq!(Amount::MIN.checked_sub(Amount::from_scaled(1)), None);
+        assert_eq!(
+            Amount::from(1).checked_sub(Amount::from(2)),
+            Some(Amount::from(-1))
+        );
+    }
+
+    #[test]
+    fn test_exact_arithmetic() {
+        let a: Amount = "0.1".parse().unwrap();
+        let b: Amount = "0.2".parse().unwrap();
 
-        assert_eq!(Amount(-10.0), -a);
+        assert_eq!("0.3".parse::<Amount>().unwrap(), a + b);
     }
 
     #[test]
     fn test_is_negative() {
-        assert_eq!(true, Amount(-1.0).is_negative());
-        assert_eq!(false, Amount(0.0).is_negative());
-        assert_eq!(false, Amount(1.0).is_negative());
+        assert!(Amount::from(-1).is_negative());
+        assert!(!Amount::from(0).is_negative());
+        assert!(!Amount::from(1).is_negative());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(Amount::from_scaled(15_000)), "1.5".parse());
+        assert_eq!(Ok(Amount::from_scaled(15_000)), "+1.5000".parse());
+        assert_eq!(Ok(Amount::from_scaled(-5_000)), "-.5".parse());
+        assert_eq!(Ok(Amount::from(2)), "2.".parse());
+        assert_eq!(Ok(Amount::from(10)), "10".parse());
+        assert_eq!(Ok(Amount::MIN), "-922337203685477.5808".parse());
+        assert_eq!(Ok(Amount::MAX), "922337203685477.5807".parse());
+
+        assert_eq!("NaN".parse::<Amount>().unwrap_err().input(), "NaN");
+        assert_matches!("".parse::<Amount>(), Err(ParseAmountError::Invalid(_)));
+        assert_matches!(".".parse::<Amount>(), Err(ParseAmountError::Invalid(_)));
+        assert_matches!("-".parse::<Amount>(), Err(ParseAmountError::Invalid(_)));
+        assert_matches!("1e3".parse::<Amount>(), Err(ParseAmountError::Invalid(_)));
+        assert_matches!("NaN".parse::<Amount>(), Err(ParseAmountError::Invalid(_)));
+        assert_matches!("inf".parse::<Amount>(), Err(ParseAmountError::Invalid(_)));
+        assert_matches!("1.2.3".parse::<Amount>(), Err(ParseAmountError::Invalid(_)));
+        assert_matches!(
+            "922337203685477.5808".parse::<Amount>(),
+            Err(ParseAmountError::OutOfRange(_))
+        );
+        assert_matches!(
+            "1000000000000000".parse::<Amount>(),
+            Err(ParseAmountError::OutOfRange(_))
+        );
+    }
+
+    #[test]
+    fn test_parse_rounding() {
+        let parse = |s| Amount::parse(s, Rounding::HalfAwayFromZero);
+
+        assert_eq!(Ok(Amount::from_scaled(1235)), parse("0.12345"));
+        assert_eq!(Ok(Amount::from_scaled(1234)), parse("0.1234499"));
+        assert_eq!(Ok(Amount::from_scaled(-1235)), parse("-0.12345"));
+        assert_eq!(Ok(Amount::from(1)), parse("0.99999"));
+        assert_eq!(Ok(Amount::ZERO), parse("0.00001"));
+        assert_matches!(
+            parse("922337203685477.58075"),
+            Err(ParseAmountError::OutOfRange(_))
+        );
+
+        let parse = |s| Amount::parse(s, Rounding::Reject);
+
+        assert_eq!(Ok(Amount::from_scaled(1234)), parse("0.1234"));
+        assert_eq!(Ok(Amount::from_scaled(1234)), parse("0.12340000"));
+        assert_matches!(parse("0.12345"), Err(ParseAmountError::TooPrecise(_)));
     }
 
     #[test]
     fn test_display() {
-        assert_eq!("0.0000".to_string(), Amount(0.0).to_string());
-        assert_eq!("0.9999".to_string(), Amount(0.9999).to_string());
-        assert_eq!("1.0000".to_string(), Amount(0.99999).to_string());
-        assert_eq!("0.1234".to_string(), Amount(0.1234).to_string());
-        assert_eq!("0.1235".to_string(), Amount(0.12345).to_string());
-        assert_eq!("1.0000".to_string(), Amount(1.000000001).to_string());
+        assert_eq!("0.0000".to_string(), Amount::ZERO.to_string());
+        assert_eq!("0.9999".to_string(), Amount::from_scaled(9999).to_string());
+        assert_eq!("0.1234".to_string(), Amount::from_scaled(1234).to_string());
+        assert_eq!("1.0000".to_string(), Amount::from(1).to_string());
+        assert_eq!(
+            "-1.5000".to_string(),
+            Amount::from_scaled(-15_000).to_string()
+        );
+        assert_eq!("-0.0001".to_string(), Amount::from_scaled(-1).to_string());
+        assert_eq!("-922337203685477.5808".to_string(), Amount::MIN.to_string());
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let parse = |s| serde_json::from_str::<Amount>(s);
+
+        assert_eq!(parse(r#""1.5""#).unwrap(), Amount::from_scaled(15_000));
+        assert_eq!(parse("2").unwrap(), Amount::from(2));
+        assert_eq!(parse("-3").unwrap(), Amount::from(-3));
+        assert_eq!(parse(r#""-0.0001""#).unwrap(), Amount::from_scaled(-1));
+
+        // Fractional numbers are rounded to f64 before they get here
+        assert!(parse("1.5").is_err());
+        assert!(parse("0.1234999999999999999").is_err());
+        assert!(parse(r#""NaN""#).is_err());
+        assert!(parse("1000000000000000").is_err());
+        assert!(parse("18446744073709551615").is_err());
+        assert!(parse("1e300").is_err());
+        assert!(parse("true").is_err());
+    }
+
+    #[test]
+    fn test_display_parse_roundtrip() {
+        for amount in [
+            Amount::ZERO,
+            Amount::MIN,
+            Amount::MAX,
+            Amount::from_scaled(-42),
+        ] {
+            assert_eq!(Ok(amount), amount.to_string().parse());
+        }
     }
 }
@@ -2,11 +2,15 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
-/// Client ID
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct Client(pub u16);
+use super::Id;
 
-impl fmt::Display for Client {
+/// Client ID. Defaults to 16 bit IDs.
+/// Wider IDs (like `Client<u64>`) and textual ones (like `Client<String>`)
+/// can be used with [Analysis][crate::Analysis] as well
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Client<C = u16>(pub C);
+
+impl<C: Id> fmt::Display for Client<C> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
     }
@@ -0,0 +1,165 @@
+use std::{fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Currency code (like `USD` or `USDT`).
+///
+/// Codes are up to [Currency::MAX_LEN] ASCII letters or digits and are uppercased
+/// on parsing, so `usd` and `USD` denote the same currency.
+/// Stored inline, so the wrapper is as cheap to copy as an [Amount][super::Amount]
+///
+/// # Example
+/// ```
+/// use scoring::event::wrappers::Currency;
+///
+/// let currency = "usd".parse::<Currency>().unwrap();
+/// assert_eq!(currency.as_str(), "USD");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+// Unused bytes are zeroes. This way derived ordering is the alphabetical one
+pub struct Currency([u8; Currency::MAX_LEN]);
+
+/// Error which can occur when parsing a [Currency]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseCurrencyError {
+    /// Input is empty
+    #[error("empty currency code")]
+    Empty,
+    /// Input is longer than [Currency::MAX_LEN]
+    #[error("currency code is longer than {} characters: {0:?}", Currency::MAX_LEN)]
+    TooLong(String),
+    /// Input has characters other than ASCII letters and digits
+    #[error("invalid currency code: {0:?}")]
+    Invalid(String),
+}
+
+impl Currency {
+    /// Maximum length of a currency code
+    pub const MAX_LEN: usize = 8;
+
+    /// Currency code
+    pub fn as_str(&self) -> &str {
+        let len = self.0.iter().position(|&b| b == 0).unwrap_or(Self::MAX_LEN);
+
+        // Only ASCII alphanumerics are ever stored
+        std::str::from_utf8(&self.0[..len]).expect("currency code is ASCII")
+    }
+}
+
+impl FromStr for Currency {
+    type Err = ParseCurrencyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseCurrencyError::Empty);
+        }
+
+        if !s.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err(ParseCurrencyError::Invalid(s.to_string()));
+        }
+
+        if s.len() > Self::MAX_LEN {
+            return Err(ParseCurrencyError::TooLong(s.to_string()));
+        }
+
+        let mut code = [0; Self::MAX_LEN];
+        for (dst, src) in code.iter_mut().zip(s.bytes()) {
+            *dst = src.to_ascii_uppercase();
+        }
+
+        Ok(Self(code))
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Currency({:?})", self.as_str())
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Currency;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a currency code")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Currency, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use assert_matches::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("USD".parse::<Currency>().unwrap().as_str(), "USD");
+        assert_eq!("usdt".parse::<Currency>().unwrap().as_str(), "USDT");
+        assert_eq!(
+            "usd".parse::<Currency>().unwrap(),
+            "USD".parse::<Currency>().unwrap()
+        );
+        assert_eq!("ABCDEFGH".parse::<Currency>().unwrap().as_str(), "ABCDEFGH");
+
+        assert_matches!("".parse::<Currency>(), Err(ParseCurrencyError::Empty));
+        assert_matches!(
+            "ABCDEFGHI".parse::<Currency>(),
+            Err(ParseCurrencyError::TooLong(_))
+        );
+        assert_matches!(
+            "US D".parse::<Currency>(),
+            Err(ParseCurrencyError::Invalid(_))
+        );
+        assert_matches!("€".parse::<Currency>(), Err(ParseCurrencyError::Invalid(_)));
+    }
+
+    #[test]
+    fn test_order() {
+        let mut currencies = ["USDT", "EUR", "USD", "A"]
+            .iter()
+            .map(|s| s.parse::<Currency>().unwrap())
+            .collect::<Vec<_>>();
+        currencies.sort();
+
+        assert_eq!(
+            currencies.iter().map(Currency::as_str).collect::<Vec<_>>(),
+            vec!["A", "EUR", "USD", "USDT"]
+        );
+    }
+
+    #[test]
+    fn test_serde() {
+        let currency = "eur".parse::<Currency>().unwrap();
+
+        assert_eq!(serde_json::to_string(&currency).unwrap(), r#""EUR""#);
+        assert_eq!(
+            serde_json::from_str::<Currency>(r#""eur""#).unwrap(),
+            currency
+        );
+        assert!(serde_json::from_str::<Currency>(r#""""#).is_err());
+    }
+}
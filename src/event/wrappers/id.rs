@@ -0,0 +1,41 @@
+use std::{fmt, hash::Hash};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Underlying type of [client][super::Client] and [transaction][super::Tx] IDs.
+///
+/// Implemented for every type with the required capabilities:
+/// unsigned integers of any width, [String] or UUIDs (like `uuid::Uuid`).
+/// IDs are cloned rather than copied, so cheap-to-copy IDs (the default ones)
+/// pay nothing for the textual ones being supported.
+/// IDs are required to be thread safe so that errors carrying them can be reported
+/// through error handling libraries (like `eyre`)
+pub trait Id:
+    Clone
+    + Eq
+    + Ord
+    + Hash
+    + fmt::Debug
+    + fmt::Display
+    + Serialize
+    + DeserializeOwned
+    + Send
+    + Sync
+    + 'static
+{
+}
+
+impl<I> Id for I where
+    I: Clone
+        + Eq
+        + Ord
+        + Hash
+        + fmt::Debug
+        + fmt::Display
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static
+{
+}
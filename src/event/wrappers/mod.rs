@@ -5,4 +5,10 @@ mod tx;
 pub use tx::Tx;
 
 mod amount;
-pub use amount::Amount;
+pub use amount::{Amount, ParseAmountError, Rounding};
+
+mod id;
+pub use id::Id;
+
+mod currency;
+pub use currency::{Currency, ParseCurrencyError};
@@ -2,11 +2,14 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
-/// Transaction ID
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct Tx(pub u32);
+use super::Id;
 
-impl fmt::Display for Tx {
+/// Transaction ID. Defaults to 32 bit IDs.
+/// Wider IDs (like `Tx<u64>`) can be used with [Analysis][crate::Analysis] as well
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Tx<T = u32>(pub T);
+
+impl<T: Id> fmt::Display for Tx<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
     }
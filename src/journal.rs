@@ -0,0 +1,733 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use serde::de;
+
+use crate::{
+    analysis::SnapshotError, event::wrappers::Id, Analysis, AnalysisConfig, AnalysisError,
+    AnalysisResult, Event,
+};
+
+/// Error which can occur while journaling or recovering [Analysis] state
+#[derive(Debug, thiserror::Error)]
+pub enum Error<C = u16, T = u32> {
+    /// Journal or snapshot file could not be written or read
+    #[error("journal io error: {0}")]
+    Io(#[from] io::Error),
+    /// Event could not be encoded into a journal record
+    #[error("failed to encode journal record: {0}")]
+    Encode(serde_json::Error),
+    /// Journal holds an invalid record which is not the last one.
+    /// Unlike a torn final record this can not be a result of a crash
+    #[error("corrupted journal record at offset {offset}")]
+    Corrupted { offset: u64 },
+    /// Snapshot could not be taken or restored
+    #[error(transparent)]
+    Snapshot(#[from] SnapshotError),
+    /// Journaled event has been rejected when replayed on top of the snapshot
+    #[error("journaled event {index} rejected on replay: {source}")]
+    Replay {
+        index: usize,
+        source: AnalysisError<C, T>,
+    },
+}
+
+/// Length of record header: payload length (`u32`) followed by payload CRC32 (`u32`)
+const RECORD_HEADER_LEN: usize = 8;
+
+/// Outcome of decoding a single journal record
+#[derive(Debug)]
+enum Record<C, T> {
+    /// Valid record holding an event. Record length is attached
+    Complete(Event<C, T>, usize),
+    /// No more records
+    End,
+    /// Final record has not been written completely
+    Torn,
+    /// Invalid record followed by more data
+    Corrupted,
+}
+
+impl<C: Id, T: Id> Record<C, T> {
+    fn encode(event: &Event<C, T>) -> Result<Vec<u8>, Error<C, T>> {
+        let payload = serde_json::to_vec(event).map_err(Error::Encode)?;
+
+        let mut record = Vec::with_capacity(RECORD_HEADER_LEN + payload.len());
+        record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        record.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        record.extend_from_slice(&payload);
+
+        Ok(record)
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        if bytes.is_empty() {
+            return Self::End;
+        }
+
+        if bytes.len() < RECORD_HEADER_LEN {
+            return Self::Torn;
+        }
+
+        let (header, rest) = bytes.split_at(RECORD_HEADER_LEN);
+        let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let checksum = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+
+        if rest.len() < len {
+            // Only the final record may be cut short. Payload is a JSON document,
+            // so the one which is complete and followed by more bytes
+            // means that the length has been corrupted rather than the write interrupted
+            let mut values =
+                serde_json::Deserializer::from_slice(rest).into_iter::<de::IgnoredAny>();
+
+            return match values.next() {
+                None => Self::Torn,
+                Some(Err(err)) if err.is_eof() => Self::Torn,
+                _ => Self::Corrupted,
+            };
+        }
+
+        let payload = &rest[..len];
+
+        if crc32fast::hash(payload) != checksum {
+            // Garbage at the very end of the journal is what a crash mid-write looks like
+            if rest.len() == len {
+                return Self::Torn;
+            }
+
+            return Self::Corrupted;
+        }
+
+        match serde_json::from_slice(payload) {
+            Ok(event) => Self::Complete(event, RECORD_HEADER_LEN + len),
+            Err(_) => Self::Corrupted,
+        }
+    }
+}
+
+/// Append-only journal of [events][Event].
+///
+/// Every record is prefixed with its length and CRC32 checksum
+/// and is synced to disk before [Journal::append] returns.
+#[derive(Debug)]
+pub struct Journal<C = u16, T = u32> {
+    #[doc(hidden)]
+    file: File,
+    #[doc(hidden)]
+    events: PhantomData<Event<C, T>>,
+}
+
+/// Journal along with events it holds
+type Recovered<C, T> = (Journal<C, T>, Vec<Event<C, T>>);
+
+impl<C: Id, T: Id> Journal<C, T> {
+    /// Opens journal at `path` (creating it if needed) and returns events it holds.
+    /// Torn final record (left by a crash in the middle of [Journal::append])
+    /// is truncated so that subsequent records are appended right after the last valid one
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Recovered<C, T>, Error<C, T>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let mut events = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            match Record::decode(&bytes[offset..]) {
+                Record::Complete(event, len) => {
+                    events.push(event);
+                    offset += len;
+                }
+                Record::End => break,
+                Record::Torn => {
+                    tracing::warn!(
+                        "truncating torn journal record: (offset: {}, len: {})",
+                        offset,
+                        bytes.len() - offset
+                    );
+
+                    file.set_len(offset as u64)?;
+                    file.sync_data()?;
+                    break;
+                }
+                Record::Corrupted => {
+                    return Err(Error::Corrupted {
+                        offset: offset as u64,
+                    })
+                }
+            }
+        }
+
+        let journal = Self {
+            file,
+            events: PhantomData,
+        };
+
+        Ok((journal, events))
+    }
+
+    /// Appends event to the journal and syncs it to disk
+    pub fn append(&mut self, event: &Event<C, T>) -> Result<(), Error<C, T>> {
+        self.file.write_all(&Record::encode(event)?)?;
+        self.file.sync_data()?;
+
+        Ok(())
+    }
+}
+
+/// [Analysis] which survives crashes.
+///
+/// State is kept in a directory holding the last snapshot
+/// and the journal of events accepted since that snapshot was taken.
+/// Both files are tagged with a generation number which is bumped by
+/// every [checkpoint][JournaledAnalysis::checkpoint]. This way a crash
+/// in the middle of a checkpoint never makes events to be applied twice.
+///
+/// # Example
+/// ```no_run
+/// use scoring::{AnalysisConfig, Event, JournaledAnalysis};
+///
+/// let mut analysis = JournaledAnalysis::open("state", AnalysisConfig::default()).unwrap();
+///
+/// let events = Vec::<Event>::new();
+/// for event in events.iter() {
+///     // Outer error means that the journal is broken and processing must stop
+///     if let Err(err) = analysis.process_event(event).unwrap() {
+///         eprintln!("event rejected: {}", err);
+///     }
+/// }
+///
+/// analysis.checkpoint().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct JournaledAnalysis<C: Id = u16, T: Id = u32> {
+    #[doc(hidden)]
+    analysis: Analysis<C, T>,
+    #[doc(hidden)]
+    journal: Journal<C, T>,
+    #[doc(hidden)]
+    dir: PathBuf,
+    #[doc(hidden)]
+    // Generation of the last snapshot. Generation 0 means that no snapshot has been taken
+    generation: u64,
+    #[doc(hidden)]
+    // Number of events in the journal of the current generation
+    journaled: usize,
+}
+
+impl<C: Id, T: Id> JournaledAnalysis<C, T> {
+    /// Recovers analysis from the state directory (creating it if needed):
+    /// loads the last snapshot (if any) and replays the journal on top of it.
+    /// Restored analysis follows specified [configuration][AnalysisConfig]
+    pub fn open<P: AsRef<Path>>(dir: P, config: AnalysisConfig) -> Result<Self, Error<C, T>> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let generation = last_generation(&dir)?;
+
+        let mut analysis = match generation {
+            0 => Analysis::new(config),
+            _ => {
+                let file = File::open(snapshot_path(&dir, generation))?;
+                Analysis::restore(BufReader::new(file), config)?
+            }
+        };
+
+        let (journal, events) = Journal::open(journal_path(&dir, generation))?;
+
+        for (index, event) in events.iter().enumerate() {
+            analysis
+                .process_event(event)
+                .map_err(|source| Error::Replay { index, source })?;
+        }
+
+        tracing::info!(
+            "analysis recovered: (generation: {}, replayed events: {})",
+            generation,
+            events.len()
+        );
+
+        remove_stale_files(&dir, generation)?;
+
+        Ok(Self {
+            analysis,
+            journal,
+            dir,
+            generation,
+            journaled: events.len(),
+        })
+    }
+
+    /// Processes [event][Event] and journals it if it has been accepted.
+    /// In-memory state never includes an event which has not been journaled.
+    ///
+    /// Outer error means that the event could not be journaled, so it has been undone.
+    /// **Journal may be left with a partially written record in this case,
+    /// so processing must not continue** (recovery truncates such a record)
+    pub fn process_event(
+        &mut self,
+        event: &Event<C, T>,
+    ) -> Result<AnalysisResult<(), C, T>, Error<C, T>> {
+        let savepoint = self.analysis.savepoint(event);
+        let result = self.analysis.process_event(event);
+
+        if result.is_ok() {
+            if let Err(err) = self.journal.append(event) {
+                self.analysis.rollback(savepoint);
+                return Err(err);
+            }
+
+            self.journaled += 1;
+        }
+
+        Ok(result)
+    }
+
+    /// Takes snapshot of the current state and starts a new empty journal
+    pub fn checkpoint(&mut self) -> Result<(), Error<C, T>> {
+        let generation = self.generation + 1;
+
+        // Snapshot is written to a temporary file first so that
+        // a crash never leaves a partially written snapshot behind
+        let tmp_path = self.dir.join("snapshot.tmp");
+        let file = File::create(&tmp_path)?;
+        self.analysis.snapshot(BufWriter::new(&file))?;
+        file.sync_all()?;
+
+        fs::rename(&tmp_path, snapshot_path(&self.dir, generation))?;
+        sync_dir(&self.dir)?;
+
+        // Journal of the new generation may only be left by an unsynced rename
+        let path = journal_path(&self.dir, generation);
+        File::create(&path)?.sync_all()?;
+        let (journal, _) = Journal::open(path)?;
+
+        self.journal = journal;
+        self.generation = generation;
+        self.journaled = 0;
+
+        tracing::info!("checkpoint taken: (generation: {})", generation);
+
+        remove_stale_files(&self.dir, generation)?;
+
+        Ok(())
+    }
+
+    /// Returns number of events journaled since the last [checkpoint][JournaledAnalysis::checkpoint]
+    /// (including the ones replayed on recovery), that is the number of events
+    /// to be replayed if analysis is recovered right now
+    pub fn journaled_events(&self) -> usize {
+        self.journaled
+    }
+
+    /// Returns current [analysis][Analysis] state
+    pub fn analysis(&self) -> &Analysis<C, T> {
+        &self.analysis
+    }
+
+    /// Turns [JournaledAnalysis] into [Analysis]. Journal is left on disk
+    pub fn into_analysis(self) -> Analysis<C, T> {
+        self.analysis
+    }
+}
+
+#[doc(hidden)]
+fn snapshot_path(dir: &Path, generation: u64) -> PathBuf {
+    dir.join(format!("snapshot-{:020}.json", generation))
+}
+
+#[doc(hidden)]
+fn journal_path(dir: &Path, generation: u64) -> PathBuf {
+    dir.join(format!("journal-{:020}.log", generation))
+}
+
+#[doc(hidden)]
+// Parses generation out of snapshot or journal file name
+fn parse_generation(name: &str) -> Option<u64> {
+    name.strip_prefix("snapshot-")
+        .and_then(|name| name.strip_suffix(".json"))
+        .or_else(|| {
+            name.strip_prefix("journal-")
+                .and_then(|name| name.strip_suffix(".log"))
+        })
+        .and_then(|generation| generation.parse().ok())
+}
+
+#[doc(hidden)]
+fn last_generation(dir: &Path) -> io::Result<u64> {
+    let mut last = 0;
+
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        let name = name.to_string_lossy();
+
+        if name.starts_with("snapshot-") {
+            if let Some(generation) = parse_generation(&name) {
+                last = last.max(generation);
+            }
+        }
+    }
+
+    Ok(last)
+}
+
+#[doc(hidden)]
+// Removes snapshots and journals of previous generations
+fn remove_stale_files(dir: &Path, generation: u64) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+
+        if let Some(stale) = parse_generation(&entry.file_name().to_string_lossy()) {
+            if stale < generation {
+                fs::remove_file(entry.path())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[doc(hidden)]
+// Makes renames within the directory durable
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::event::wrappers::{Amount, Client, Tx};
+
+    use assert_matches::assert_matches;
+
+    fn state_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("scoring-journal-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn deposit(client: u16, tx: u32, amount: i32) -> Event {
+        Event::Deposit {
+            client: Client(client),
+            tx: Tx(tx),
+            amount: Amount::from(amount),
+            currency: None,
+        }
+    }
+
+    fn journal_len(dir: &Path, generation: u64) -> u64 {
+        fs::metadata(journal_path(dir, generation)).unwrap().len()
+    }
+
+    fn truncate_journal(dir: &Path, generation: u64, len: u64) {
+        let file = OpenOptions::new()
+            .write(true)
+            .open(journal_path(dir, generation))
+            .unwrap();
+        file.set_len(len).unwrap();
+    }
+
+    #[test]
+    fn test_recover() {
+        let dir = state_dir("recover");
+
+        let mut expected = Analysis::begin();
+        let mut analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+
+        let events = [
+            deposit(1, 1, 10),
+            deposit(1, 2, 5),
+            Event::Dispute {
+                client: Client(1),
+                tx: Tx(2),
+                currency: None,
+            },
+            Event::Freeze {
+                client: Client(1),
+                reason: "audit".to_string(),
+            },
+        ];
+
+        for event in events.iter() {
+            assert_eq!(expected.process_event(event), Ok(()));
+            assert_matches!(analysis.process_event(event), Ok(Ok(())));
+        }
+
+        drop(analysis);
+
+        let analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+        assert_eq!(analysis.analysis(), &expected);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejected_events_not_journaled() {
+        let dir = state_dir("rejected");
+
+        let mut analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+
+        assert_matches!(analysis.process_event(&deposit(1, 1, 10)), Ok(Ok(())));
+        let len = journal_len(&dir, 0);
+
+        assert_matches!(
+            analysis.process_event(&deposit(1, 1, 10)),
+            Ok(Err(AnalysisError::DuplicateOperation(Tx(1))))
+        );
+        assert_eq!(journal_len(&dir, 0), len);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_append() {
+        let dir = state_dir("failed_append");
+
+        let mut analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+
+        assert_matches!(analysis.process_event(&deposit(1, 1, 10)), Ok(Ok(())));
+        assert_matches!(analysis.process_event(&deposit(1, 2, 5)), Ok(Ok(())));
+        let dispute = Event::Dispute {
+            client: Client(1),
+            tx: Tx(2),
+            currency: None,
+        };
+        assert_matches!(analysis.process_event(&dispute), Ok(Ok(())));
+        let expected = analysis.analysis().clone();
+
+        // Writes to a read only file fail
+        analysis.journal.file = File::open(journal_path(&dir, 0)).unwrap();
+
+        let events = [
+            deposit(2, 3, 1),
+            deposit(1, 3, 1),
+            Event::Withdrawal {
+                client: Client(1),
+                tx: Tx(3),
+                amount: Amount::from(1),
+                currency: None,
+            },
+            Event::Dispute {
+                client: Client(1),
+                tx: Tx(1),
+                currency: None,
+            },
+            Event::Resolve {
+                client: Client(1),
+                tx: Tx(2),
+                currency: None,
+            },
+            Event::Chargeback {
+                client: Client(1),
+                tx: Tx(2),
+                currency: None,
+            },
+            Event::Freeze {
+                client: Client(1),
+                reason: "audit".to_string(),
+            },
+        ];
+
+        for event in events.iter() {
+            assert_matches!(analysis.process_event(event), Err(Error::Io(_)));
+            assert_eq!(analysis.analysis(), &expected);
+            assert_eq!(analysis.journaled_events(), 3);
+        }
+
+        drop(analysis);
+
+        let analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+        assert_eq!(analysis.analysis(), &expected);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_torn_record() {
+        let dir = state_dir("torn_record");
+
+        let mut analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+
+        assert_matches!(analysis.process_event(&deposit(1, 1, 10)), Ok(Ok(())));
+        assert_matches!(analysis.process_event(&deposit(1, 2, 5)), Ok(Ok(())));
+        let len = journal_len(&dir, 0);
+        assert_matches!(analysis.process_event(&deposit(1, 3, 1)), Ok(Ok(())));
+
+        drop(analysis);
+
+        // Simulate crash in the middle of the last record payload
+        truncate_journal(&dir, 0, journal_len(&dir, 0) - 3);
+
+        let mut analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+        assert_eq!(journal_len(&dir, 0), len);
+
+        let mut expected = Analysis::begin();
+        assert_eq!(expected.process_event(&deposit(1, 1, 10)), Ok(()));
+        assert_eq!(expected.process_event(&deposit(1, 2, 5)), Ok(()));
+        assert_eq!(analysis.analysis(), &expected);
+
+        // Lost event can be submitted again
+        assert_matches!(analysis.process_event(&deposit(1, 3, 1)), Ok(Ok(())));
+        assert_eq!(expected.process_event(&deposit(1, 3, 1)), Ok(()));
+
+        drop(analysis);
+
+        let analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+        assert_eq!(analysis.analysis(), &expected);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_torn_record_header() {
+        let dir = state_dir("torn_record_header");
+
+        let mut analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+
+        assert_matches!(analysis.process_event(&deposit(1, 1, 10)), Ok(Ok(())));
+        let len = journal_len(&dir, 0);
+        assert_matches!(analysis.process_event(&deposit(1, 2, 5)), Ok(Ok(())));
+
+        drop(analysis);
+
+        // Simulate crash in the middle of the last record header
+        truncate_journal(&dir, 0, len + 5);
+
+        let analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+        assert_eq!(journal_len(&dir, 0), len);
+
+        let mut expected = Analysis::begin();
+        assert_eq!(expected.process_event(&deposit(1, 1, 10)), Ok(()));
+        assert_eq!(analysis.analysis(), &expected);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_record() {
+        let dir = state_dir("corrupted_record");
+
+        let mut analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+
+        assert_matches!(analysis.process_event(&deposit(1, 1, 10)), Ok(Ok(())));
+        assert_matches!(analysis.process_event(&deposit(1, 2, 5)), Ok(Ok(())));
+
+        drop(analysis);
+
+        // Flip a byte of the first record payload
+        let path = journal_path(&dir, 0);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[RECORD_HEADER_LEN] ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+
+        let result = JournaledAnalysis::<u16, u32>::open(&dir, AnalysisConfig::default());
+        assert_matches!(result, Err(Error::Corrupted { offset: 0 }));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_record_length() {
+        let dir = state_dir("corrupted_record_length");
+
+        let mut analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+
+        assert_matches!(analysis.process_event(&deposit(1, 1, 10)), Ok(Ok(())));
+        let len = journal_len(&dir, 0);
+        assert_matches!(analysis.process_event(&deposit(1, 2, 5)), Ok(Ok(())));
+        assert_matches!(analysis.process_event(&deposit(1, 3, 1)), Ok(Ok(())));
+
+        drop(analysis);
+
+        // Make the middle record claim more bytes than the rest of the journal holds
+        let path = journal_path(&dir, 0);
+        let mut bytes = fs::read(&path).unwrap();
+        let offset = len as usize;
+        bytes[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+
+        let result = JournaledAnalysis::<u16, u32>::open(&dir, AnalysisConfig::default());
+        assert_matches!(result, Err(Error::Corrupted { offset }) if offset == len);
+
+        // Valid records are not truncated
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let dir = state_dir("checkpoint");
+
+        let mut expected = Analysis::begin();
+        let mut analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+
+        for event in [deposit(1, 1, 10), deposit(2, 2, 5)].iter() {
+            assert_eq!(expected.process_event(event), Ok(()));
+            assert_matches!(analysis.process_event(event), Ok(Ok(())));
+        }
+
+        assert_eq!(analysis.journaled_events(), 2);
+        analysis.checkpoint().unwrap();
+        assert_eq!(analysis.journaled_events(), 0);
+
+        assert!(!journal_path(&dir, 0).exists());
+        assert!(snapshot_path(&dir, 1).exists());
+        assert_eq!(journal_len(&dir, 1), 0);
+
+        let event = Event::Withdrawal {
+            client: Client(1),
+            tx: Tx(3),
+            amount: Amount::from(4),
+            currency: None,
+        };
+        assert_eq!(expected.process_event(&event), Ok(()));
+        assert_matches!(analysis.process_event(&event), Ok(Ok(())));
+
+        drop(analysis);
+
+        let analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+        assert_eq!(analysis.analysis(), &expected);
+        assert_eq!(analysis.journaled_events(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint_interrupted() {
+        let dir = state_dir("checkpoint_interrupted");
+
+        let mut analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+
+        assert_matches!(analysis.process_event(&deposit(1, 1, 10)), Ok(Ok(())));
+        let expected = analysis.analysis().clone();
+
+        // Simulate crash right after the snapshot of the next generation has been written
+        let file = File::create(snapshot_path(&dir, 1)).unwrap();
+        expected.snapshot(&file).unwrap();
+
+        drop(analysis);
+
+        let analysis = JournaledAnalysis::open(&dir, AnalysisConfig::default()).unwrap();
+        assert_eq!(analysis.analysis(), &expected);
+        assert!(!journal_path(&dir, 0).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,6 +2,13 @@
 pub mod analysis;
 /// provides [Event] which is what [Analysis] operates on
 pub mod event;
+/// provides [JournaledAnalysis] which recovers [Analysis] state after crashes
+pub mod journal;
 
-pub use analysis::{AccountSummary, Analysis, AnalysisError, AnalysisResult, AnalysisSummary};
-pub use event::{Event, RawEvent};
+pub use analysis::{
+    AccountBalance, AccountSummary, AccountView, Analysis, AnalysisConfig, AnalysisError,
+    AnalysisResult, AnalysisSummary, LockedAccountPolicy, OperationKind, OperationState,
+    OperationView, SnapshotError, SummaryOrder, TxUniqueness, WithdrawalDisputePolicy,
+};
+pub use event::{Event, ParseEventError, RawEvent};
+pub use journal::JournaledAnalysis;
@@ -14,6 +14,11 @@ command() {
     }
 }
 
+# Same as `command` but output order is preserved
+command_ordered() {
+    cargo run --release -- "${@:2}" $CASES/$1/input.csv 2>/dev/null
+}
+
 expected() {
     {
         head -n 1 $CASES/$1/output.csv
@@ -146,3 +151,222 @@ expected() {
     expected="$(expected locked_account)"
     assert_output "$expected"; assert_not_equal "$expected" ""
 }
+
+@test "exact arithmetic" {
+    run -0 command exact_arithmetic
+    expected="$(expected exact_arithmetic)"
+    assert_output "$expected"; assert_not_equal "$expected" ""
+}
+
+@test "order by client" {
+    run -0 command_ordered amount_rounding
+    assert_output "$(cat $CASES/amount_rounding/output.csv)"
+}
+
+@test "order by total" {
+    run -0 command_ordered order_by_total --order total
+    assert_output "$(cat $CASES/order_by_total/output.csv)"
+}
+
+@test "rejects" {
+    rejects="$BATS_TEST_TMPDIR/rejects.csv"
+    run -0 command_ordered rejects --rejects "$rejects"
+    assert_output "$(cat $CASES/rejects/output.csv)"
+    assert_equal "$(cat $rejects)" "$(cat $CASES/rejects/rejects.csv)"
+}
+
+@test "freeze unlock" {
+    run -0 command freeze_unlock
+    expected="$(expected freeze_unlock)"
+    assert_output "$expected"; assert_not_equal "$expected" ""
+}
+
+@test "snapshot" {
+    snapshot="$BATS_TEST_TMPDIR/snapshot.json"
+    cargo run --release -- --snapshot-out "$snapshot" $CASES/snapshot/first.csv >/dev/null 2>&1
+    run -0 command_ordered snapshot --snapshot-in "$snapshot"
+    assert_output "$(cat $CASES/snapshot/output.csv)"
+}
+
+@test "snapshot version 1" {
+    run -0 command_ordered snapshot --snapshot-in $CASES/snapshot/v1.json
+    assert_output "$(cat $CASES/snapshot/output.csv)"
+}
+
+@test "server" {
+    cargo build --release 2>/dev/null
+    ./target/release/server --tcp 127.0.0.1:47878 2>/dev/null &
+    server=$!
+    sleep 1
+
+    exec 3<>/dev/tcp/127.0.0.1/47878
+    cat $CASES/server/requests.jsonl >&3
+    run head -n "$(wc -l < $CASES/server/replies.jsonl)" <&3
+    exec 3>&-
+    kill $server
+
+    assert_output "$(cat $CASES/server/replies.jsonl)"
+}
+
+@test "server restart" {
+    cargo build --release 2>/dev/null
+    state="$BATS_TEST_TMPDIR/state"
+    ./target/release/server --tcp 127.0.0.1:47879 --state-dir "$state" 2>/dev/null &
+    server=$!
+    sleep 1
+
+    exec 3<>/dev/tcp/127.0.0.1/47879
+    cat $CASES/server_restart/requests.jsonl >&3
+    run head -n "$(wc -l < $CASES/server_restart/replies.jsonl)" <&3
+    exec 3>&-
+    assert_output "$(cat $CASES/server_restart/replies.jsonl)"
+
+    # Shutdown takes the final checkpoint, so nothing is left to replay
+    wait $server
+    assert [ -e "$state/snapshot-00000000000000000002.json" ]
+    assert [ ! -s "$state/journal-00000000000000000002.log" ]
+
+    ./target/release/server --tcp 127.0.0.1:47879 --state-dir "$state" 2>/dev/null &
+    server=$!
+    sleep 1
+
+    exec 3<>/dev/tcp/127.0.0.1/47879
+    printf 'balance 1\n{"type":"deposit","client":1,"tx":2,"amount":"1"}\n' >&3
+    run head -n "$(wc -l < $CASES/server_restart/restart_replies.jsonl)" <&3
+    exec 3>&-
+    kill $server
+
+    assert_output "$(cat $CASES/server_restart/restart_replies.jsonl)"
+}
+
+@test "report every" {
+    run -0 command_ordered report_every --report-every 2 --report-prefix "$BATS_TEST_TMPDIR/report"
+    assert_output "$(cat $CASES/report_every/output.csv)"
+    assert_equal "$(cat $BATS_TEST_TMPDIR/report-2.csv)" "$(cat $CASES/report_every/report-2.csv)"
+    assert_equal "$(cat $BATS_TEST_TMPDIR/report-4.csv)" "$(cat $CASES/report_every/report-4.csv)"
+    assert [ ! -e "$BATS_TEST_TMPDIR/report-6.csv" ]
+}
+
+@test "jsonl input jsonl output" {
+    run -0 cargo run --release -- --output-format jsonl $CASES/jsonl/input.jsonl 2>/dev/null
+    assert_output "$(cat $CASES/jsonl/output.jsonl)"
+}
+
+@test "jsonl input json output" {
+    run -0 cargo run --release -- --input-format jsonl --output-format json $CASES/jsonl/input.jsonl 2>/dev/null
+    assert_output "$(cat $CASES/jsonl/output.json)"
+}
+
+@test "multiple inputs" {
+    run -0 bash -c "cat $CASES/multiple_inputs/shard_2.csv | cargo run --release -- $CASES/multiple_inputs/shard_1.csv - 2>/dev/null"
+    assert_output "$(cat $CASES/multiple_inputs/output.csv)"
+}
+
+@test "gzip input" {
+    run -0 cargo run --release --features compression -- $CASES/compressed/input.csv.gz 2>/dev/null
+    assert_output "$(cat $CASES/compressed/output.csv)"
+}
+
+@test "zstd input" {
+    run -0 cargo run --release --features compression -- $CASES/compressed/input.csv.zst 2>/dev/null
+    assert_output "$(cat $CASES/compressed/output.csv)"
+}
+
+@test "compressed input without compression feature" {
+    run cargo run --release -- $CASES/compressed/input.csv.gz 2>/dev/null
+    assert_failure
+}
+
+@test "custom dialect" {
+    run -0 command_ordered custom_dialect --delimiter ';' --no-header --no-output-header
+    assert_output "$(cat $CASES/custom_dialect/output.csv)"
+}
+
+@test "output file" {
+    output_file="$BATS_TEST_TMPDIR/output.csv"
+    run -0 command_ordered example --output "$output_file"
+    assert_output ""
+    assert_equal "$(cat $output_file)" "$(cat $CASES/example/output.csv)"
+}
+
+@test "abort failure policy" {
+    run command_ordered example --failure-policy abort
+    assert_failure
+    assert_output ""
+}
+
+@test "strict parse mode" {
+    run command_ordered malformed_rows
+    assert_failure
+    assert_output ""
+}
+
+@test "lenient parse mode" {
+    rejects="$BATS_TEST_TMPDIR/rejects.csv"
+    run -0 command_ordered malformed_rows --parse-mode lenient --rejects "$rejects"
+    assert_output "$(cat $CASES/malformed_rows/output.csv)"
+    assert_equal "$(cat $rejects)" "$(cat $CASES/malformed_rows/rejects.csv)"
+}
+
+@test "lenient parse mode jsonl" {
+    rejects="$BATS_TEST_TMPDIR/rejects.csv"
+    run -0 cargo run --release -- --parse-mode lenient --rejects "$rejects" $CASES/malformed_rows/input.jsonl 2>/dev/null
+    assert_output "$(cat $CASES/malformed_rows/output.csv)"
+    assert_equal "$(cat $rejects)" "$(cat $CASES/malformed_rows/rejects_jsonl.csv)"
+}
+
+@test "amount validation" {
+    rejects="$BATS_TEST_TMPDIR/rejects.csv"
+    run -0 command_ordered amount_validation --parse-mode lenient --max-amount 1000000 --reject-imprecise-amounts --rejects "$rejects"
+    assert_output "$(cat $CASES/amount_validation/output.csv)"
+    assert_equal "$(cat $rejects)" "$(cat $CASES/amount_validation/rejects.csv)"
+}
+
+@test "amount reject codes" {
+    rejects="$BATS_TEST_TMPDIR/rejects.csv"
+    run -0 command_ordered amount_rejects --parse-mode lenient --max-amount 1000 --reject-imprecise-amounts --rejects "$rejects"
+    assert_output "$(cat $CASES/amount_rejects/output.csv)"
+    assert_equal "$(cat $rejects)" "$(cat $CASES/amount_rejects/rejects.csv)"
+}
+
+@test "unexpected fields ignored" {
+    run -0 command_ordered unexpected_fields
+    assert_output "$(cat $CASES/unexpected_fields/output.csv)"
+}
+
+@test "unexpected fields warn" {
+    rejects="$BATS_TEST_TMPDIR/rejects.csv"
+    run -0 command_ordered unexpected_fields --unexpected-fields warn --rejects "$rejects"
+    assert_output "$(cat $CASES/unexpected_fields/output.csv)"
+    assert_equal "$(cat $rejects)" "$(cat $CASES/unexpected_fields/rejects_warn.csv)"
+}
+
+@test "unexpected fields reject" {
+    rejects="$BATS_TEST_TMPDIR/rejects.csv"
+    run -0 command_ordered unexpected_fields --unexpected-fields reject --parse-mode lenient --rejects "$rejects"
+    assert_output "$(cat $CASES/unexpected_fields/output_reject.csv)"
+    assert_equal "$(cat $rejects)" "$(cat $CASES/unexpected_fields/rejects_reject.csv)"
+}
+
+@test "wide ids" {
+    run -0 command_ordered wide_ids --client-ids u64 --tx-ids u64
+    assert_output "$(cat $CASES/wide_ids/output.csv)"
+}
+
+@test "wide ids do not fit default types" {
+    run command_ordered wide_ids
+    assert_failure
+    assert_output ""
+}
+
+@test "string ids" {
+    run -0 command_ordered string_ids --client-ids string --tx-ids string
+    assert_output "$(cat $CASES/string_ids/output.csv)"
+}
+
+@test "multi currency" {
+    rejects="$BATS_TEST_TMPDIR/rejects.csv"
+    run -0 command_ordered multi_currency --rejects "$rejects"
+    assert_output "$(cat $CASES/multi_currency/output.csv)"
+    assert_equal "$(cat $rejects)" "$(cat $CASES/multi_currency/rejects.csv)"
+}